@@ -0,0 +1,83 @@
+use nalgebra_glm::Vec3;
+
+// Caja alineada a los ejes que envuelve a un objeto; la usa el BVH para descartar rayos
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Aabb {
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        Aabb { min, max }
+    }
+
+    // Caja que no contiene nada; es el elemento neutro de `unir`
+    pub fn vacia() -> Self {
+        Aabb {
+            min: Vec3::repeat(f32::INFINITY),
+            max: Vec3::repeat(f32::NEG_INFINITY),
+        }
+    }
+
+    // Para objetos sin límites, como un plano infinito
+    pub fn infinita() -> Self {
+        Aabb {
+            min: Vec3::repeat(f32::NEG_INFINITY),
+            max: Vec3::repeat(f32::INFINITY),
+        }
+    }
+
+    pub fn es_finita(&self) -> bool {
+        self.min
+            .iter()
+            .chain(self.max.iter())
+            .all(|c| c.is_finite())
+    }
+
+    pub fn unir(&self, otra: &Aabb) -> Aabb {
+        Aabb {
+            min: self.min.inf(&otra.min),
+            max: self.max.sup(&otra.max),
+        }
+    }
+
+    pub fn incluir_punto(&self, punto: &Vec3) -> Aabb {
+        Aabb {
+            min: self.min.inf(punto),
+            max: self.max.sup(punto),
+        }
+    }
+
+    pub fn centro(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn eje_mas_largo(&self) -> usize {
+        let tamano = self.max - self.min;
+        if tamano.x >= tamano.y && tamano.x >= tamano.z {
+            0
+        } else if tamano.y >= tamano.z {
+            1
+        } else {
+            2
+        }
+    }
+
+    // Prueba de las losas: devuelve la distancia de entrada si el rayo toca la caja antes de `t_max`
+    pub fn intersecta(&self, ray_origin: &Vec3, inv_dir: &Vec3, t_max: f32) -> Option<f32> {
+        let mut t_entrada = 0.0_f32;
+        let mut t_salida = t_max;
+        for i in 0..3 {
+            let t0 = (self.min[i] - ray_origin[i]) * inv_dir[i];
+            let t1 = (self.max[i] - ray_origin[i]) * inv_dir[i];
+            t_entrada = t_entrada.max(t0.min(t1));
+            t_salida = t_salida.min(t0.max(t1));
+        }
+        if t_entrada <= t_salida {
+            Some(t_entrada)
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,379 @@
+use crate::aabb::Aabb;
+use crate::ray_intersect::{Intersect, RayIntersect};
+use nalgebra_glm::Vec3;
+
+const OBJETOS_POR_HOJA: usize = 4;
+
+enum NodoBvh {
+    Hoja {
+        caja: Aabb,
+        inicio: usize,
+        cantidad: usize,
+    },
+    Interno {
+        caja: Aabb,
+        izquierdo: usize,
+        derecho: usize,
+    },
+}
+
+impl NodoBvh {
+    fn caja(&self) -> &Aabb {
+        match self {
+            NodoBvh::Hoja { caja, .. } | NodoBvh::Interno { caja, .. } => caja,
+        }
+    }
+}
+
+// Jerarquía de volúmenes envolventes sobre un conjunto de objetos.
+// Se comporta como un objeto más, así que puede reemplazar a la lista de objetos en cast_ray.
+pub struct Bvh {
+    objetos: Vec<Box<dyn RayIntersect>>,
+    nodos: Vec<NodoBvh>,
+    // Índices de `objetos` ordenados de forma que cada hoja cubre un rango contiguo
+    indices: Vec<usize>,
+    // Objetos sin caja finita (planos infinitos); se prueban siempre
+    ilimitados: Vec<usize>,
+}
+
+impl Bvh {
+    pub fn new(objetos: Vec<Box<dyn RayIntersect>>) -> Self {
+        let cajas: Vec<Aabb> = objetos.iter().map(|objeto| objeto.aabb()).collect();
+        let (mut indices, ilimitados): (Vec<usize>, Vec<usize>) =
+            (0..objetos.len()).partition(|&i| cajas[i].es_finita());
+
+        let mut nodos = Vec::new();
+        if !indices.is_empty() {
+            let cantidad = indices.len();
+            construir(&cajas, &mut indices, 0, cantidad, &mut nodos);
+        }
+
+        Bvh {
+            objetos,
+            nodos,
+            indices,
+            ilimitados,
+        }
+    }
+
+    // Tras modificar objetos hay que llamar a `reajustar` antes de volver a lanzar rayos
+    pub fn objeto_mut(&mut self, indice: usize) -> &mut dyn RayIntersect {
+        self.objetos[indice].as_mut()
+    }
+
+    // Los objetos en el orden en que se agregaron; el índice de cada uno es su posición
+    pub fn objetos_mut(&mut self) -> &mut [Box<dyn RayIntersect>] {
+        &mut self.objetos
+    }
+
+    // Agrega un objeto y devuelve su índice, que no cambia después. El objeto no entra en la
+    // jerarquía hasta llamar a `reconstruir`, así se pueden agregar muchos de una vez.
+    pub fn agregar(&mut self, objeto: Box<dyn RayIntersect>) -> usize {
+        self.objetos.push(objeto);
+        self.objetos.len() - 1
+    }
+
+    pub fn reconstruir(&mut self) {
+        *self = Bvh::new(std::mem::take(&mut self.objetos));
+    }
+
+    // Recalcula las cajas de abajo hacia arriba sin cambiar la forma del árbol.
+    // Sirve cuando los objetos se movieron poco; los hijos siempre tienen índice mayor que su padre.
+    pub fn reajustar(&mut self) {
+        for nodo in (0..self.nodos.len()).rev() {
+            let caja = match self.nodos[nodo] {
+                NodoBvh::Hoja {
+                    inicio, cantidad, ..
+                } => self.indices[inicio..inicio + cantidad]
+                    .iter()
+                    .fold(Aabb::vacia(), |caja, &i| caja.unir(&self.objetos[i].aabb())),
+                NodoBvh::Interno {
+                    izquierdo, derecho, ..
+                } => self.nodos[izquierdo]
+                    .caja()
+                    .unir(self.nodos[derecho].caja()),
+            };
+            match &mut self.nodos[nodo] {
+                NodoBvh::Hoja { caja: actual, .. } | NodoBvh::Interno { caja: actual, .. } => {
+                    *actual = caja
+                }
+            }
+        }
+    }
+}
+
+// Construye el subárbol para indices[inicio..inicio + cantidad] y devuelve el índice de su nodo.
+// Divide por la mediana de los centros sobre el eje más largo.
+fn construir(
+    cajas: &[Aabb],
+    indices: &mut [usize],
+    inicio: usize,
+    cantidad: usize,
+    nodos: &mut Vec<NodoBvh>,
+) -> usize {
+    let rango = &mut indices[inicio..inicio + cantidad];
+    let caja = rango
+        .iter()
+        .fold(Aabb::vacia(), |caja, &i| caja.unir(&cajas[i]));
+
+    if cantidad <= OBJETOS_POR_HOJA {
+        nodos.push(NodoBvh::Hoja {
+            caja,
+            inicio,
+            cantidad,
+        });
+        return nodos.len() - 1;
+    }
+
+    let caja_centros = rango.iter().fold(Aabb::vacia(), |caja, &i| {
+        caja.incluir_punto(&cajas[i].centro())
+    });
+    let eje = caja_centros.eje_mas_largo();
+    let mitad = cantidad / 2;
+    rango.select_nth_unstable_by(mitad, |&a, &b| {
+        cajas[a].centro()[eje].total_cmp(&cajas[b].centro()[eje])
+    });
+
+    // Se reserva el lugar del nodo interno antes de construir los hijos
+    let nodo = nodos.len();
+    nodos.push(NodoBvh::Hoja {
+        caja,
+        inicio,
+        cantidad,
+    });
+    let izquierdo = construir(cajas, indices, inicio, mitad, nodos);
+    let derecho = construir(cajas, indices, inicio + mitad, cantidad - mitad, nodos);
+    nodos[nodo] = NodoBvh::Interno {
+        caja,
+        izquierdo,
+        derecho,
+    };
+    nodo
+}
+
+impl RayIntersect for Bvh {
+    fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Intersect {
+        let mut intersect = Intersect::empty();
+        let mut zbuffer = f32::INFINITY;
+
+        let probar = |indice: usize, intersect: &mut Intersect, zbuffer: &mut f32| {
+            let tmp = self.objetos[indice].ray_intersect(ray_origin, ray_direction);
+            if tmp.is_intersecting && tmp.distance < *zbuffer {
+                *zbuffer = tmp.distance;
+                *intersect = tmp;
+            }
+        };
+
+        for &indice in &self.ilimitados {
+            probar(indice, &mut intersect, &mut zbuffer);
+        }
+
+        if self.nodos.is_empty() {
+            return intersect;
+        }
+
+        let inv_dir = Vec3::new(
+            1.0 / ray_direction.x,
+            1.0 / ray_direction.y,
+            1.0 / ray_direction.z,
+        );
+        let mut pila = vec![0];
+        while let Some(nodo) = pila.pop() {
+            if self.nodos[nodo]
+                .caja()
+                .intersecta(ray_origin, &inv_dir, zbuffer)
+                .is_none()
+            {
+                continue;
+            }
+
+            match self.nodos[nodo] {
+                NodoBvh::Hoja {
+                    inicio, cantidad, ..
+                } => {
+                    for &indice in &self.indices[inicio..inicio + cantidad] {
+                        probar(indice, &mut intersect, &mut zbuffer);
+                    }
+                }
+                NodoBvh::Interno {
+                    izquierdo, derecho, ..
+                } => {
+                    // Se visita primero el hijo más cercano para recortar antes al otro
+                    let t_izq = self.nodos[izquierdo]
+                        .caja()
+                        .intersecta(ray_origin, &inv_dir, zbuffer);
+                    let t_der = self.nodos[derecho]
+                        .caja()
+                        .intersecta(ray_origin, &inv_dir, zbuffer);
+                    match (t_izq, t_der) {
+                        (Some(a), Some(b)) if a <= b => pila.extend([derecho, izquierdo]),
+                        (Some(_), Some(_)) => pila.extend([izquierdo, derecho]),
+                        (Some(_), None) => pila.push(izquierdo),
+                        (None, Some(_)) => pila.push(derecho),
+                        (None, None) => {}
+                    }
+                }
+            }
+        }
+
+        intersect
+    }
+
+    // Las UV las calcula cada objeto al intersectar; el BVH no tiene superficie propia
+    fn get_uv(&self, _point: &Vec3) -> (f32, f32) {
+        (0.0, 0.0)
+    }
+
+    fn aabb(&self) -> Aabb {
+        if !self.ilimitados.is_empty() {
+            return Aabb::infinita();
+        }
+        self.nodos
+            .first()
+            .map(|nodo| *nodo.caja())
+            .unwrap_or_else(Aabb::vacia)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+    use crate::cube::Cube;
+    use crate::material::Material;
+    use crate::sphere::Sphere;
+    use std::time::Instant;
+
+    // Generador congruencial lineal para que la prueba sea reproducible sin depender de rand
+    struct Lcg(u64);
+
+    impl Lcg {
+        fn siguiente(&mut self) -> f32 {
+            self.0 = self
+                .0
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (self.0 >> 40) as f32 / (1u64 << 24) as f32
+        }
+
+        fn rango(&mut self, min: f32, max: f32) -> f32 {
+            min + (max - min) * self.siguiente()
+        }
+    }
+
+    fn escena_aleatoria(rng: &mut Lcg, cantidad: usize) -> Vec<Box<dyn RayIntersect>> {
+        let material = Material::new(Color::new(200, 200, 200), 10.0, [0.9, 0.1], None);
+        (0..cantidad)
+            .map(|i| {
+                let centro = Vec3::new(
+                    rng.rango(-20.0, 20.0),
+                    rng.rango(-20.0, 20.0),
+                    rng.rango(-20.0, 20.0),
+                );
+                let objeto: Box<dyn RayIntersect> = if i % 2 == 0 {
+                    Box::new(Cube::new(
+                        centro,
+                        rng.rango(0.2, 1.0),
+                        material.clone(),
+                        Vec3::zeros(),
+                    ))
+                } else {
+                    Box::new(Sphere {
+                        center: centro,
+                        radius: rng.rango(0.1, 0.6),
+                        material: material.clone(),
+                    })
+                };
+                objeto
+            })
+            .collect()
+    }
+
+    // Rayos desde puntos al azar hacia la zona donde están los objetos
+    fn rayos_aleatorios(rng: &mut Lcg, cantidad: usize) -> Vec<(Vec3, Vec3)> {
+        (0..cantidad)
+            .map(|_| {
+                let origen = Vec3::new(
+                    rng.rango(-30.0, 30.0),
+                    rng.rango(-30.0, 30.0),
+                    rng.rango(-30.0, 30.0),
+                );
+                let objetivo = Vec3::new(
+                    rng.rango(-20.0, 20.0),
+                    rng.rango(-20.0, 20.0),
+                    rng.rango(-20.0, 20.0),
+                );
+                (origen, (objetivo - origen).normalize())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn bvh_coincide_con_fuerza_bruta() {
+        // La misma semilla genera dos copias idénticas de la escena
+        let objetos = escena_aleatoria(&mut Lcg(7), 1000);
+        let bvh = Bvh::new(escena_aleatoria(&mut Lcg(7), 1000));
+        let rayos = rayos_aleatorios(&mut Lcg(13), 500);
+
+        let mut impactos = 0;
+        for (origen, direccion) in &rayos {
+            let esperado = objetos.ray_intersect(origen, direccion);
+            let obtenido = bvh.ray_intersect(origen, direccion);
+            assert_eq!(esperado.is_intersecting, obtenido.is_intersecting);
+            if esperado.is_intersecting {
+                impactos += 1;
+                assert!((esperado.distance - obtenido.distance).abs() < 1e-4);
+                assert!((esperado.point - obtenido.point).magnitude() < 1e-4);
+            }
+        }
+        assert!(impactos > 0);
+    }
+
+    // Depende de la máquina, por eso no corre con el resto: `cargo test -- --ignored`
+    #[test]
+    #[ignore]
+    fn bvh_es_mas_rapido_que_fuerza_bruta() {
+        let objetos = escena_aleatoria(&mut Lcg(7), 1000);
+        let bvh = Bvh::new(escena_aleatoria(&mut Lcg(7), 1000));
+        let rayos = rayos_aleatorios(&mut Lcg(13), 500);
+        let medir = |escena: &dyn RayIntersect| {
+            let inicio = Instant::now();
+            for (origen, direccion) in &rayos {
+                escena.ray_intersect(origen, direccion);
+            }
+            inicio.elapsed()
+        };
+
+        let tiempo_fuerza_bruta = medir(&objetos);
+        let tiempo_bvh = medir(&bvh);
+
+        assert!(
+            tiempo_bvh < tiempo_fuerza_bruta,
+            "fuerza bruta {:?}, bvh {:?}",
+            tiempo_fuerza_bruta,
+            tiempo_bvh
+        );
+    }
+
+    #[test]
+    fn bvh_vacio_no_intersecta() {
+        let bvh = Bvh::new(Vec::new());
+
+        let intersect = bvh.ray_intersect(&Vec3::zeros(), &Vec3::new(0.0, 0.0, -1.0));
+
+        assert!(!intersect.is_intersecting);
+    }
+
+    #[test]
+    fn caja_del_bvh_envuelve_a_todos_los_objetos() {
+        let mut rng = Lcg(11);
+        let objetos = escena_aleatoria(&mut rng, 50);
+        let cajas: Vec<Aabb> = objetos.iter().map(|objeto| objeto.aabb()).collect();
+        let bvh = Bvh::new(objetos);
+
+        let caja = bvh.aabb();
+        for c in cajas {
+            assert_eq!(caja.unir(&c), caja);
+        }
+    }
+}
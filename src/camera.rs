@@ -2,10 +2,53 @@
 use nalgebra_glm::Vec3;
 use std::f32::consts::PI;
 
+// Límites del zoom, para que la imagen no se invierta ni se vuelva un punto
+const FOV_MINIMO: f32 = PI / 36.0;
+const FOV_MAXIMO: f32 = PI * 0.9;
+const ALTO_ORTOGRAFICO_MINIMO: f32 = 0.1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Proyeccion {
+    // Los rayos salen del ojo y se abren según el campo de visión
+    Perspectiva,
+    // Los rayos son paralelos y salen de un rectángulo centrado en el ojo; los objetos no se
+    // achican con la distancia (vistas isométricas)
+    Ortografica,
+}
+
+// Qué hacen las flechas y el mouse en la ventana
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModoCamara {
+    // Giran el ojo alrededor de `centro`
+    Orbita,
+    // Giran la vista desde el ojo, en primera persona
+    Vuelo,
+}
+
+impl ModoCamara {
+    pub fn alternar(self) -> Self {
+        match self {
+            ModoCamara::Orbita => ModoCamara::Vuelo,
+            ModoCamara::Vuelo => ModoCamara::Orbita,
+        }
+    }
+}
+
 pub struct Camera {
     pub ojo: Vec3,
     pub centro: Vec3,
-    pub arriba: Vec3
+    pub arriba: Vec3,
+    pub proyeccion: Proyeccion,
+    // Campo de visión vertical en radianes, para la perspectiva
+    pub fov: f32,
+    // Alto del área visible en unidades de la escena, para la ortográfica
+    pub alto_ortografico: f32,
+    // Ancho / alto de la imagen; None usa la proporción del framebuffer
+    pub aspecto: Option<f32>,
+    // Radio de la lente; con 0 todo sale enfocado, como en una cámara estenopeica
+    pub apertura: f32,
+    // Distancia desde el ojo (a lo largo de la vista) del plano que queda nítido
+    pub distancia_focal: f32,
 }
 
 impl Camera {
@@ -13,18 +56,87 @@ impl Camera {
         Camera {
             ojo,
             centro,
-            arriba
+            arriba,
+            proyeccion: Proyeccion::Perspectiva,
+            fov: PI / 3.0,
+            alto_ortografico: 5.0,
+            aspecto: None,
+            apertura: 0.0,
+            distancia_focal: (centro - ojo).magnitude(),
         }
     }
 
     pub fn base_change(&self, vector: &Vec3) -> Vec3 {
+        let (right, arriba, forward) = self.ejes();
+
+        let rotated = vector.x * right + vector.y * arriba - vector.z * forward;
+
+        rotated.normalize()
+    }
+
+    // Derecha, arriba y adelante de la cámara
+    fn ejes(&self) -> (Vec3, Vec3, Vec3) {
         let forward = (self.centro - self.ojo).normalize();
         let right = forward.cross(&self.arriba).normalize();
         let arriba = right.cross(&forward).normalize();
+        (right, arriba, forward)
+    }
 
-        let rotated = vector.x * right + vector.y * arriba - vector.z * forward;
+    // Origen y dirección del rayo que pasa por (x, y) de la pantalla, ambos en [-1, 1] con y
+    // hacia arriba. `aspecto_imagen` se usa si la cámara no fija el suyo. `lente` es el punto de
+    // la lente por el que sale el rayo, en [0, 1)²; solo importa si hay apertura.
+    pub fn rayo(&self, x: f32, y: f32, aspecto_imagen: f32, lente: (f32, f32)) -> (Vec3, Vec3) {
+        let (origen, direccion) = self.rayo_estenopeico(x, y, aspecto_imagen);
+        if self.apertura <= 0.0 {
+            return (origen, direccion);
+        }
+
+        // Lente delgada: todos los rayos del píxel se cruzan en el plano focal, así que lo que
+        // está sobre él sale nítido y el resto se difumina
+        let (right, arriba, forward) = self.ejes();
+        let enfocado = origen + direccion * (self.distancia_focal / direccion.dot(&forward));
+        let radio = self.apertura * lente.0.sqrt();
+        let angulo = 2.0 * PI * lente.1;
+        let origen = origen + right * (radio * angulo.cos()) + arriba * (radio * angulo.sin());
+        (origen, (enfocado - origen).normalize())
+    }
+
+    fn rayo_estenopeico(&self, x: f32, y: f32, aspecto_imagen: f32) -> (Vec3, Vec3) {
+        let aspecto = self.aspecto.unwrap_or(aspecto_imagen);
+        match self.proyeccion {
+            Proyeccion::Perspectiva => {
+                let escala = (self.fov * 0.5).tan();
+                let direccion = Vec3::new(x * aspecto * escala, y * escala, -1.0);
+                (self.ojo, self.base_change(&direccion))
+            }
+            Proyeccion::Ortografica => {
+                let (right, arriba, forward) = self.ejes();
+                let mitad = self.alto_ortografico * 0.5;
+                let origen = self.ojo + right * (x * aspecto * mitad) + arriba * (y * mitad);
+                (origen, forward)
+            }
+        }
+    }
+
+    // factor > 1 acerca la imagen y < 1 la aleja, sin mover el ojo
+    pub fn zoom(&mut self, factor: f32) {
+        match self.proyeccion {
+            Proyeccion::Perspectiva => {
+                let escala = (self.fov * 0.5).tan() / factor;
+                self.fov = (2.0 * escala.atan()).clamp(FOV_MINIMO, FOV_MAXIMO);
+            }
+            Proyeccion::Ortografica => {
+                self.alto_ortografico =
+                    (self.alto_ortografico / factor).max(ALTO_ORTOGRAFICO_MINIMO);
+            }
+        }
+    }
 
-        return rotated.normalize();
+    pub fn alternar_proyeccion(&mut self) {
+        self.proyeccion = match self.proyeccion {
+            Proyeccion::Perspectiva => Proyeccion::Ortografica,
+            Proyeccion::Ortografica => Proyeccion::Perspectiva,
+        };
     }
 
     pub fn orbit(&mut self, delta_yaw: f32, delta_pitch: f32) {
@@ -49,6 +161,24 @@ impl Camera {
 
 
 
+    // Gira la vista desde el ojo: el yaw positivo hacia la derecha y el pitch positivo hacia
+    // arriba. `centro` se mueve para seguir a la misma distancia del ojo.
+    pub fn mirar(&mut self, delta_yaw: f32, delta_pitch: f32) {
+        let vista = self.centro - self.ojo;
+        let distancia = vista.magnitude();
+
+        let yaw = vista.z.atan2(vista.x) + delta_yaw;
+        let pitch = (vista.y / distancia).asin() + delta_pitch;
+        let pitch = pitch.clamp(-PI / 2.0 + 0.1, PI / 2.0 - 0.1);
+
+        self.centro = self.ojo
+            + Vec3::new(
+                distancia * yaw.cos() * pitch.cos(),
+                distancia * pitch.sin(),
+                distancia * yaw.sin() * pitch.cos(),
+            );
+    }
+
     /////////////////////////////////////////////////////////////////////////////////
 
     fn mover(&mut self, direccion: Vec3, distancia: f32) {
@@ -79,4 +209,113 @@ impl Camera {
         self.mover(-right, distance);
     }
 
-}
\ No newline at end of file
+    pub fn mover_arriba(&mut self, distance: f32) {
+        self.mover(self.arriba, distance);
+    }
+
+    pub fn mover_abajo(&mut self, distance: f32) {
+        self.mover(-self.arriba, distance);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn camara() -> Camera {
+        Camera::new(
+            Vec3::new(0.0, 0.0, 5.0),
+            Vec3::zeros(),
+            Vec3::new(0.0, 1.0, 0.0),
+        )
+    }
+
+    #[test]
+    fn la_perspectiva_abre_los_rayos_segun_el_fov() {
+        let camara = camara();
+
+        let (origen, centro) = camara.rayo(0.0, 0.0, 2.0, (0.0, 0.0));
+        let (_, arriba) = camara.rayo(0.0, 1.0, 2.0, (0.0, 0.0));
+
+        assert_eq!(origen, camara.ojo);
+        assert!((centro - Vec3::new(0.0, 0.0, -1.0)).magnitude() < 1e-6);
+        // El borde superior queda a medio fov del centro
+        assert!((arriba.angle(&centro) - camara.fov / 2.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn la_ortografica_lanza_rayos_paralelos() {
+        let mut camara = camara();
+        camara.alternar_proyeccion();
+        camara.aspecto = Some(1.0);
+
+        let (origen, direccion) = camara.rayo(1.0, -1.0, 2.0, (0.0, 0.0));
+
+        assert_eq!(camara.proyeccion, Proyeccion::Ortografica);
+        assert!((direccion - Vec3::new(0.0, 0.0, -1.0)).magnitude() < 1e-6);
+        assert!((origen - Vec3::new(2.5, -2.5, 5.0)).magnitude() < 1e-5);
+    }
+
+    #[test]
+    fn los_rayos_de_la_lente_se_cruzan_en_el_plano_focal() {
+        let mut camara = camara();
+        camara.apertura = 0.5;
+        camara.distancia_focal = 3.0;
+
+        // El centro de la imagen está enfocado a 3 unidades del ojo
+        let foco = Vec3::new(0.0, 0.0, 2.0);
+        for lente in [(0.0, 0.0), (1.0, 0.0), (0.5, 0.25), (0.9, 0.7)] {
+            let (origen, direccion) = camara.rayo(0.0, 0.0, 1.0, lente);
+            let t = (origen.z - foco.z) / -direccion.z;
+
+            assert!((origen - camara.ojo).magnitude() <= camara.apertura + 1e-5);
+            assert!((origen + direccion * t - foco).magnitude() < 1e-5);
+        }
+        let (borde, _) = camara.rayo(0.0, 0.0, 1.0, (1.0, 0.0));
+        assert!((borde - Vec3::new(0.5, 0.0, 5.0)).magnitude() < 1e-5);
+    }
+
+    #[test]
+    fn mirar_gira_la_vista_sin_mover_el_ojo() {
+        let mut camara = camara();
+
+        camara.mirar(PI / 2.0, 0.0);
+        assert_eq!(camara.ojo, Vec3::new(0.0, 0.0, 5.0));
+        assert!(
+            (camara.centro - Vec3::new(5.0, 0.0, 5.0)).magnitude() < 1e-4,
+            "{:?}",
+            camara.centro
+        );
+
+        camara.mirar(0.0, PI);
+        let vista = (camara.centro - camara.ojo).normalize();
+        assert!((vista.y - (PI / 2.0 - 0.1).sin()).abs() < 1e-4);
+        assert!(((camara.centro - camara.ojo).magnitude() - 5.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn subir_y_bajar_mueven_ojo_y_centro() {
+        let mut camara = camara();
+
+        camara.mover_arriba(2.0);
+        camara.mover_abajo(0.5);
+
+        assert_eq!(camara.ojo, Vec3::new(0.0, 1.5, 5.0));
+        assert_eq!(camara.centro, Vec3::new(0.0, 1.5, 0.0));
+        assert_eq!(ModoCamara::Orbita.alternar(), ModoCamara::Vuelo);
+    }
+
+    #[test]
+    fn el_zoom_cierra_el_fov_y_el_area_ortografica() {
+        let mut camara = camara();
+
+        camara.zoom(2.0);
+        assert!(((camara.fov * 0.5).tan() - (PI / 6.0).tan() / 2.0).abs() < 1e-5);
+        camara.zoom(1e6);
+        assert_eq!(camara.fov, FOV_MINIMO);
+
+        camara.alternar_proyeccion();
+        camara.zoom(0.5);
+        assert_eq!(camara.alto_ortografico, 10.0);
+    }
+}
@@ -70,6 +70,19 @@ impl Mul<f32> for Color {
     }
 }
 
+// Implementación de la multiplicación componente a componente (modula un color por otro)
+impl Mul<Color> for Color {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Self {
+            r: ((self.r as u16 * other.r as u16) / 255) as u8,
+            g: ((self.g as u16 * other.g as u16) / 255) as u8,
+            b: ((self.b as u16 * other.b as u16) / 255) as u8,
+        }
+    }
+}
+
 // Implementación de la conversión a string
 impl fmt::Display for Color {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
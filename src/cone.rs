@@ -0,0 +1,168 @@
+use crate::aabb::Aabb;
+use crate::material::Material;
+use crate::ray_intersect::{Intersect, RayIntersect};
+use nalgebra_glm::Vec3;
+use std::f32::consts::PI;
+
+const EPSILON_BASE: f32 = 1e-3;
+
+// Cono con base, vertical: la base está centrada en `centro` y la punta `altura` más arriba
+#[derive(Clone)]
+pub struct Cone {
+    pub centro: Vec3,
+    pub radio: f32,
+    pub altura: f32,
+    pub material: Material,
+}
+
+impl Cone {
+    pub fn new(centro: Vec3, radio: f32, altura: f32, material: Material) -> Self {
+        Cone {
+            centro,
+            radio,
+            altura,
+            material,
+        }
+    }
+}
+
+impl RayIntersect for Cone {
+    fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Intersect {
+        let o = ray_origin - self.centro;
+        let d = ray_direction;
+        let k = self.radio / self.altura;
+        let k2 = k * k;
+        let mut mejor: Option<(f32, Vec3)> = None;
+        let mut considerar = |t: f32, normal: Vec3| {
+            if t > 0.0 && mejor.is_none_or(|(actual, _)| t < actual) {
+                mejor = Some((t, normal));
+            }
+        };
+
+        // Manto: x² + z² = k² (h - y)² con 0 <= y <= h
+        let h = self.altura - o.y;
+        let a = d.x * d.x + d.z * d.z - k2 * d.y * d.y;
+        let b = 2.0 * (o.x * d.x + o.z * d.z + k2 * h * d.y);
+        let c = o.x * o.x + o.z * o.z - k2 * h * h;
+        let raices = if a.abs() < 1e-8 {
+            // Rayo paralelo a una generatriz: la ecuación queda lineal
+            if b != 0.0 {
+                vec![-c / b]
+            } else {
+                vec![]
+            }
+        } else {
+            let discriminante = b * b - 4.0 * a * c;
+            if discriminante >= 0.0 {
+                let raiz = discriminante.sqrt();
+                vec![(-b - raiz) / (2.0 * a), (-b + raiz) / (2.0 * a)]
+            } else {
+                vec![]
+            }
+        };
+        for t in raices {
+            let p = o + d * t;
+            if (0.0..=self.altura).contains(&p.y) {
+                let rho = (p.x * p.x + p.z * p.z).sqrt();
+                let normal = if rho > 1e-6 {
+                    Vec3::new(p.x, k * rho, p.z).normalize()
+                } else {
+                    Vec3::new(0.0, 1.0, 0.0)
+                };
+                considerar(t, normal);
+            }
+        }
+
+        // Base: disco en y = 0
+        if d.y != 0.0 {
+            let t = -o.y / d.y;
+            let p = o + d * t;
+            if p.x * p.x + p.z * p.z <= self.radio * self.radio {
+                considerar(t, Vec3::new(0.0, -1.0, 0.0));
+            }
+        }
+
+        match mejor {
+            Some((t, normal)) => {
+                let punto = ray_origin + ray_direction * t;
+                let (u, v) = self.get_uv(&punto);
+                let (tangente, bitangente) = if normal == Vec3::new(0.0, -1.0, 0.0) {
+                    let diametro = 2.0 * self.radio;
+                    (Vec3::new(diametro, 0.0, 0.0), Vec3::new(0.0, 0.0, diametro))
+                } else {
+                    // u da una vuelta al círculo de esa altura y v baja por toda la generatriz,
+                    // desde la punta hasta la base
+                    let p = punto - self.centro;
+                    let rho = (p.x * p.x + p.z * p.z).sqrt().max(1e-6);
+                    (
+                        Vec3::new(p.z, 0.0, -p.x) * (2.0 * PI),
+                        Vec3::new(self.radio * p.x / rho, -self.altura, self.radio * p.z / rho),
+                    )
+                };
+                Intersect::new(punto, normal, t, self.material.clone(), u, v)
+                    .con_tangentes(tangente, bitangente)
+            }
+            None => Intersect::empty(),
+        }
+    }
+
+    // En el manto u da la vuelta al eje y v baja desde la punta; la base se proyecta desde abajo
+    fn get_uv(&self, point: &Vec3) -> (f32, f32) {
+        let p = point - self.centro;
+        if p.y.abs() < EPSILON_BASE {
+            let u = p.x / (2.0 * self.radio) + 0.5;
+            let v = p.z / (2.0 * self.radio) + 0.5;
+            return (u, v);
+        }
+        let u = 0.5 - p.z.atan2(p.x) / (2.0 * PI);
+        let v = (self.altura - p.y) / self.altura;
+        (u, v)
+    }
+
+    fn aabb(&self) -> Aabb {
+        Aabb::new(
+            self.centro - Vec3::new(self.radio, 0.0, self.radio),
+            self.centro + Vec3::new(self.radio, self.altura, self.radio),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+
+    fn cono() -> Cone {
+        let material = Material::new(Color::new(255, 255, 255), 10.0, [0.9, 0.1], None);
+        Cone::new(Vec3::zeros(), 1.0, 2.0, material)
+    }
+
+    #[test]
+    fn el_manto_se_estrecha_hacia_la_punta() {
+        let cono = cono();
+        let direccion = Vec3::new(0.0, 0.0, -1.0);
+
+        // A media altura el radio es 0.5
+        let medio = cono.ray_intersect(&Vec3::new(0.0, 1.0, 5.0), &direccion);
+        let fuera = cono.ray_intersect(&Vec3::new(0.6, 1.0, 5.0), &direccion);
+
+        assert!((medio.distance - 4.5).abs() < 1e-4, "{}", medio.distance);
+        let esperada = Vec3::new(0.0, 0.5, 1.0).normalize();
+        assert!(
+            (medio.normal - esperada).magnitude() < 1e-4,
+            "{:?}",
+            medio.normal
+        );
+        assert!((medio.v - 0.5).abs() < 1e-4);
+        assert!(!fuera.is_intersecting);
+    }
+
+    #[test]
+    fn la_base_se_ve_desde_abajo() {
+        let hit = cono().ray_intersect(&Vec3::new(0.2, -3.0, 0.0), &Vec3::new(0.0, 1.0, 0.0));
+
+        assert!((hit.distance - 3.0).abs() < 1e-5);
+        assert_eq!(hit.normal, Vec3::new(0.0, -1.0, 0.0));
+        assert!((hit.u - 0.6).abs() < 1e-5 && (hit.v - 0.5).abs() < 1e-5);
+    }
+}
@@ -1,13 +1,42 @@
 use nalgebra_glm::{Vec3,vec3};
+use crate::aabb::Aabb;
 use crate::material::Material;
 use crate::ray_intersect::{Intersect, RayIntersect};
 
+// Caras del cubo, en el orden en que se guardan sus materiales
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cara {
+    Derecha,   // +X
+    Izquierda, // -X
+    Arriba,    // +Y
+    Abajo,     // -Y
+    Frente,    // +Z
+    Atras,     // -Z
+}
+
+impl Cara {
+    pub fn normal(self) -> Vec3 {
+        match self {
+            Cara::Derecha => Vec3::new(1.0, 0.0, 0.0),
+            Cara::Izquierda => Vec3::new(-1.0, 0.0, 0.0),
+            Cara::Arriba => Vec3::new(0.0, 1.0, 0.0),
+            Cara::Abajo => Vec3::new(0.0, -1.0, 0.0),
+            Cara::Frente => Vec3::new(0.0, 0.0, 1.0),
+            Cara::Atras => Vec3::new(0.0, 0.0, -1.0),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct Cube {
     pub center: Vec3,
     pub size: f32,
     pub material: Material,
+    // Material propio de cada cara, en el orden de `Cara`; las que no tienen usan `material`
+    pub materiales_caras: [Option<Material>; 6],
     pub velocidad:Vec3,
+    // Fracción de la velocidad que conserva al rebotar (0 no rebota, 1 rebota sin perder nada)
+    pub restitucion: f32,
 }
 
 impl Cube {
@@ -16,7 +45,9 @@ impl Cube {
             center,
             size,
             material,
+            materiales_caras: Default::default(),
             velocidad,
+            restitucion: 0.3,
         }
     }
 
@@ -25,34 +56,42 @@ impl Cube {
         self.velocidad += gravedad * delta_tiempo; // Actualizamos la velocidad con la gravedad
         self.center += self.velocidad * delta_tiempo; // Actualizamos la posición
     }
-    fn get_uv(&self, punto_encuentro: &Vec3) -> (f32, f32) {
-        let mitad = self.size / 2.0;
-        let min = self.center - Vec3::new(mitad, mitad, mitad);
-        let max = self.center + Vec3::new(mitad, mitad, mitad);
-        let mut u = 0.0;
-        let mut v = 0.0;
-        if (punto_encuentro.x - min.x).abs() < 0.001 { 
-            u = (punto_encuentro.z - min.z) / (max.z - min.z);
-            v = (punto_encuentro.y - min.y) / (max.y - min.y);
-        } else if (punto_encuentro.x - max.x).abs() < 0.001 { 
-            u = (punto_encuentro.z - min.z) / (max.z - min.z);
-            v = (punto_encuentro.y - min.y) / (max.y - min.y);
-        } else if (punto_encuentro.y - min.y).abs() < 0.001 { 
-            u = (punto_encuentro.x - min.x) / (max.x - min.x);
-            v = (punto_encuentro.z - min.z) / (max.z - min.z);
-        } else if (punto_encuentro.y - max.y).abs() < 0.001 { 
-            u = (punto_encuentro.x - min.x) / (max.x - min.x);
-            v = (punto_encuentro.z - min.z) / (max.z - min.z);
-        } else if (punto_encuentro.z - min.z).abs() < 0.001 { 
-            u = (punto_encuentro.x - min.x) / (max.x - min.x);
-            v = (punto_encuentro.y - min.y) / (max.y - min.y);
-        } else if (punto_encuentro.z - max.z).abs() < 0.001 { 
-            u = (punto_encuentro.x - min.x) / (max.x - min.x);
-            v = (punto_encuentro.y - min.y) / (max.y - min.y);
+
+    // Material de la cara si tiene uno propio, o el del cubo si no
+    pub fn material_de(&self, cara: Cara) -> &Material {
+        self.materiales_caras[cara as usize]
+            .as_ref()
+            .unwrap_or(&self.material)
+    }
+
+    pub fn asignar_material_cara(&mut self, cara: Cara, material: Material) {
+        self.materiales_caras[cara as usize] = Some(material);
+    }
+
+    // La cara en la que está el punto es la del eje en el que más se aleja del centro
+    fn cara_en(&self, punto: &Vec3) -> Cara {
+        let relativo = punto - self.center;
+        let eje = relativo.iamax();
+        match (eje, relativo[eje] >= 0.0) {
+            (0, true) => Cara::Derecha,
+            (0, false) => Cara::Izquierda,
+            (1, true) => Cara::Arriba,
+            (1, false) => Cara::Abajo,
+            (_, true) => Cara::Frente,
+            (_, false) => Cara::Atras,
         }
-        (u, v)
     }
+}
 
+// Hacia dónde crecen u y v en la cara con esa normal
+fn ejes_cara(normal: &Vec3) -> (Vec3, Vec3) {
+    if normal.x != 0.0 {
+        (Vec3::new(0.0, 0.0, 1.0), Vec3::new(0.0, 1.0, 0.0))
+    } else if normal.y != 0.0 {
+        (Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 1.0))
+    } else {
+        (Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0))
+    }
 }
 
 impl RayIntersect for Cube {
@@ -74,53 +113,84 @@ impl RayIntersect for Cube {
 
         let t_hit = if t1 < 0.0 { t2 } else { t1 };
         let punto_encuentro = ray_origin + ray_direction * t_hit;
-        let mut normal = Vec3::new(0.0, 0.0, 0.0);
-        for i in 0..3 {
-            if (punto_encuentro[i] - min[i]).abs() < 0.001 {
-                normal[i] = -1.0;
-            } else if (punto_encuentro[i] - max[i]).abs() < 0.001 {
-                normal[i] = 1.0;
-            }
-        }
+        let cara = self.cara_en(&punto_encuentro);
+        let normal = cara.normal();
 
         let (u, v) = self.get_uv(&punto_encuentro);
-        let u = u.clamp(0.0, 1.0);
-        let v = v.clamp(0.0, 1.0);
+        let (tangente, bitangente) = ejes_cara(&normal);
 
         Intersect::new(
             punto_encuentro,
             normal,
             t_hit,
-            self.material.clone(),
+            self.material_de(cara).clone(),
             u,
             v
         )
+        .con_tangentes(tangente * self.size, bitangente * self.size)
     }
+
     fn get_uv(&self, punto_encuentro: &Vec3) -> (f32, f32) {
-        let mitad = self.size / 2.0;
-        let min = self.center - Vec3::new(mitad, mitad, mitad);
-        let max = self.center + Vec3::new(mitad, mitad, mitad);
-        let mut u = 0.0;
-        let mut v = 0.0;
-        if (punto_encuentro.x - min.x).abs() < 0.001 { 
-            u = (punto_encuentro.z - min.z) / (max.z - min.z);
-            v = (punto_encuentro.y - min.y) / (max.y - min.y);
-        } else if (punto_encuentro.x - max.x).abs() < 0.001 { 
-            u = (punto_encuentro.z - min.z) / (max.z - min.z);
-            v = (punto_encuentro.y - min.y) / (max.y - min.y);
-        } else if (punto_encuentro.y - min.y).abs() < 0.001 { 
-            u = (punto_encuentro.x - min.x) / (max.x - min.x);
-            v = (punto_encuentro.z - min.z) / (max.z - min.z);
-        } else if (punto_encuentro.y - max.y).abs() < 0.001 { 
-            u = (punto_encuentro.x - min.x) / (max.x - min.x);
-            v = (punto_encuentro.z - min.z) / (max.z - min.z);
-        } else if (punto_encuentro.z - min.z).abs() < 0.001 { 
-            u = (punto_encuentro.x - min.x) / (max.x - min.x);
-            v = (punto_encuentro.y - min.y) / (max.y - min.y);
-        } else if (punto_encuentro.z - max.z).abs() < 0.001 { 
-            u = (punto_encuentro.x - min.x) / (max.x - min.x);
-            v = (punto_encuentro.y - min.y) / (max.y - min.y);
-        }
-        (u, v)
+        let (tangente, bitangente) = ejes_cara(&self.cara_en(punto_encuentro).normal());
+        let relativo = (punto_encuentro - self.center) / self.size;
+        (
+            relativo.dot(&tangente) + 0.5,
+            relativo.dot(&bitangente) + 0.5,
+        )
+    }
+
+    fn aabb(&self) -> Aabb {
+        let mitad = Vec3::repeat(self.size / 2.0);
+        Aabb::new(self.center - mitad, self.center + mitad)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+
+    fn material(color: Color) -> Material {
+        Material::new(color, 10.0, [0.9, 0.1], None)
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn cada_cara_usa_su_material() {
+        let mut cubo = Cube::new(
+            Vec3::zeros(),
+            1.0,
+            material(Color::new(100, 60, 20)),
+            Vec3::zeros(),
+        );
+        cubo.asignar_material_cara(Cara::Arriba, material(Color::new(0, 200, 0)));
+
+        let arriba = cubo.ray_intersect(&Vec3::new(0.0, 5.0, 0.0), &Vec3::new(0.0, -1.0, 0.0));
+        let lado = cubo.ray_intersect(&Vec3::new(5.0, 0.0, 0.0), &Vec3::new(-1.0, 0.0, 0.0));
+
+        assert_eq!(arriba.normal, Cara::Arriba.normal());
+        assert_eq!(arriba.material.diffuse, Color::new(0, 200, 0));
+        assert_eq!(lado.normal, Cara::Derecha.normal());
+        assert_eq!(lado.material.diffuse, Color::new(100, 60, 20));
+    }
+
+    #[test]
+    fn las_uv_recorren_cada_cara_de_arista_a_arista() {
+        let cubo = Cube::new(
+            Vec3::zeros(),
+            2.0,
+            material(Color::new(255, 255, 255)),
+            Vec3::zeros(),
+        );
+
+        // En la cara +Z, u sigue a +X y v a +Y
+        let frente = cubo.ray_intersect(&Vec3::new(0.9, 0.9, 5.0), &Vec3::new(0.0, 0.0, -1.0));
+        // En la cara -X, u sigue a +Z y v a +Y
+        let izquierda = cubo.ray_intersect(&Vec3::new(-5.0, -0.9, 0.9), &Vec3::new(1.0, 0.0, 0.0));
+
+        assert!((frente.u - 0.95).abs() < 1e-5 && (frente.v - 0.95).abs() < 1e-5);
+        assert!((izquierda.u - 0.95).abs() < 1e-5 && (izquierda.v - 0.05).abs() < 1e-5);
+        // Una unidad de u o de v recorre toda la arista
+        assert!((frente.tangente - Vec3::new(2.0, 0.0, 0.0)).magnitude() < 1e-5);
+        assert!((frente.bitangente - Vec3::new(0.0, 2.0, 0.0)).magnitude() < 1e-5);
+    }
+}
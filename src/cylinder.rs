@@ -0,0 +1,146 @@
+use crate::aabb::Aabb;
+use crate::material::Material;
+use crate::ray_intersect::{Intersect, RayIntersect};
+use nalgebra_glm::Vec3;
+use std::f32::consts::PI;
+
+// Tolerancia para decidir si un punto está sobre una de las tapas
+const EPSILON_TAPA: f32 = 1e-3;
+
+// Cilindro con tapas, vertical (eje Y) y centrado en `centro`
+#[derive(Clone)]
+pub struct Cylinder {
+    pub centro: Vec3,
+    pub radio: f32,
+    pub altura: f32,
+    pub material: Material,
+}
+
+impl Cylinder {
+    pub fn new(centro: Vec3, radio: f32, altura: f32, material: Material) -> Self {
+        Cylinder {
+            centro,
+            radio,
+            altura,
+            material,
+        }
+    }
+}
+
+impl RayIntersect for Cylinder {
+    fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Intersect {
+        let o = ray_origin - self.centro;
+        let d = ray_direction;
+        let mitad = self.altura / 2.0;
+        let mut mejor: Option<(f32, Vec3)> = None;
+        let mut considerar = |t: f32, normal: Vec3| {
+            if t > 0.0 && mejor.is_none_or(|(actual, _)| t < actual) {
+                mejor = Some((t, normal));
+            }
+        };
+
+        // Pared: x² + z² = r² con -h/2 <= y <= h/2
+        let a = d.x * d.x + d.z * d.z;
+        if a > 0.0 {
+            let b = 2.0 * (o.x * d.x + o.z * d.z);
+            let c = o.x * o.x + o.z * o.z - self.radio * self.radio;
+            let discriminante = b * b - 4.0 * a * c;
+            if discriminante >= 0.0 {
+                let raiz = discriminante.sqrt();
+                for t in [(-b - raiz) / (2.0 * a), (-b + raiz) / (2.0 * a)] {
+                    let p = o + d * t;
+                    if p.y.abs() <= mitad {
+                        considerar(t, Vec3::new(p.x, 0.0, p.z) / self.radio);
+                    }
+                }
+            }
+        }
+
+        // Tapas: discos en y = ±h/2
+        if d.y != 0.0 {
+            for signo in [1.0, -1.0] {
+                let t = (signo * mitad - o.y) / d.y;
+                let p = o + d * t;
+                if p.x * p.x + p.z * p.z <= self.radio * self.radio {
+                    considerar(t, Vec3::new(0.0, signo, 0.0));
+                }
+            }
+        }
+
+        match mejor {
+            Some((t, normal)) => {
+                let punto = ray_origin + ray_direction * t;
+                let (u, v) = self.get_uv(&punto);
+                // En las tapas u sigue a X y v a Z; en la pared u da la vuelta y v baja
+                let (tangente, bitangente) = if normal.y != 0.0 {
+                    let diametro = 2.0 * self.radio;
+                    (Vec3::new(diametro, 0.0, 0.0), Vec3::new(0.0, 0.0, diametro))
+                } else {
+                    (
+                        Vec3::new(normal.z, 0.0, -normal.x) * (2.0 * PI * self.radio),
+                        Vec3::new(0.0, -self.altura, 0.0),
+                    )
+                };
+                Intersect::new(punto, normal, t, self.material.clone(), u, v)
+                    .con_tangentes(tangente, bitangente)
+            }
+            None => Intersect::empty(),
+        }
+    }
+
+    // En la pared u da la vuelta al eje y v baja desde la tapa superior; en las tapas la
+    // textura se proyecta desde arriba
+    fn get_uv(&self, point: &Vec3) -> (f32, f32) {
+        let p = point - self.centro;
+        let mitad = self.altura / 2.0;
+        if (p.y.abs() - mitad).abs() < EPSILON_TAPA {
+            let u = p.x / (2.0 * self.radio) + 0.5;
+            let v = p.z / (2.0 * self.radio) + 0.5;
+            return (u, v);
+        }
+        let u = 0.5 - p.z.atan2(p.x) / (2.0 * PI);
+        let v = (mitad - p.y) / self.altura;
+        (u, v)
+    }
+
+    fn aabb(&self) -> Aabb {
+        let extension = Vec3::new(self.radio, self.altura / 2.0, self.radio);
+        Aabb::new(self.centro - extension, self.centro + extension)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+
+    fn cilindro() -> Cylinder {
+        let material = Material::new(Color::new(255, 255, 255), 10.0, [0.9, 0.1], None);
+        Cylinder::new(Vec3::zeros(), 1.0, 2.0, material)
+    }
+
+    #[test]
+    fn choca_con_la_pared_y_con_las_tapas() {
+        let cilindro = cilindro();
+
+        let pared = cilindro.ray_intersect(&Vec3::new(0.0, 0.5, 5.0), &Vec3::new(0.0, 0.0, -1.0));
+        let tapa = cilindro.ray_intersect(&Vec3::new(0.3, 5.0, 0.0), &Vec3::new(0.0, -1.0, 0.0));
+        let encima = cilindro.ray_intersect(&Vec3::new(0.0, 1.5, 5.0), &Vec3::new(0.0, 0.0, -1.0));
+
+        assert!((pared.distance - 4.0).abs() < 1e-5);
+        assert!((pared.normal - Vec3::new(0.0, 0.0, 1.0)).magnitude() < 1e-5);
+        assert!((pared.v - 0.25).abs() < 1e-5);
+        assert!((tapa.distance - 4.0).abs() < 1e-5);
+        assert_eq!(tapa.normal, Vec3::new(0.0, 1.0, 0.0));
+        assert!((tapa.u - 0.65).abs() < 1e-5);
+        assert!(!encima.is_intersecting);
+    }
+
+    #[test]
+    fn desde_adentro_choca_con_la_salida() {
+        let hit = cilindro().ray_intersect(&Vec3::zeros(), &Vec3::new(1.0, 0.0, 0.0));
+
+        assert!((hit.distance - 1.0).abs() < 1e-5);
+        assert!((hit.normal - Vec3::new(1.0, 0.0, 0.0)).magnitude() < 1e-5);
+    }
+}
@@ -0,0 +1,90 @@
+use crate::aabb::Aabb;
+use crate::material::Material;
+use crate::plane::{ejes_tangentes, interseccion_plano};
+use crate::ray_intersect::{Intersect, RayIntersect};
+use nalgebra_glm::Vec3;
+
+#[derive(Clone)]
+pub struct Disk {
+    pub centro: Vec3,
+    pub normal: Vec3,
+    pub radio: f32,
+    pub material: Material,
+}
+
+impl Disk {
+    pub fn new(centro: Vec3, normal: Vec3, radio: f32, material: Material) -> Self {
+        Disk {
+            centro,
+            normal: normal.normalize(),
+            radio,
+            material,
+        }
+    }
+}
+
+impl RayIntersect for Disk {
+    fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Intersect {
+        let t = match interseccion_plano(ray_origin, ray_direction, &self.centro, &self.normal) {
+            Some(t) => t,
+            None => return Intersect::empty(),
+        };
+
+        let punto = ray_origin + ray_direction * t;
+        if (punto - self.centro).magnitude_squared() > self.radio * self.radio {
+            return Intersect::empty();
+        }
+        let normal = if self.normal.dot(ray_direction) > 0.0 {
+            -self.normal
+        } else {
+            self.normal
+        };
+        let (u, v) = self.get_uv(&punto);
+        let (tangente, bitangente) = ejes_tangentes(&self.normal);
+        let diametro = 2.0 * self.radio;
+
+        Intersect::new(punto, normal, t, self.material.clone(), u, v)
+            .con_tangentes(tangente * diametro, bitangente * diametro)
+    }
+
+    // La textura se proyecta sobre el cuadrado que envuelve al disco
+    fn get_uv(&self, point: &Vec3) -> (f32, f32) {
+        let (tangente, bitangente) = ejes_tangentes(&self.normal);
+        let relativo = (point - self.centro) / (2.0 * self.radio);
+        (
+            relativo.dot(&tangente) + 0.5,
+            relativo.dot(&bitangente) + 0.5,
+        )
+    }
+
+    fn aabb(&self) -> Aabb {
+        // Cuánto se extiende el disco en cada eje según su inclinación
+        let extension = self
+            .normal
+            .map(|n| self.radio * (1.0 - n * n).max(0.0).sqrt());
+        Aabb::new(self.centro - extension, self.centro + extension)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+
+    #[test]
+    fn solo_choca_dentro_del_radio() {
+        let material = Material::new(Color::new(255, 255, 255), 10.0, [0.9, 0.1], None);
+        let disco = Disk::new(Vec3::zeros(), Vec3::new(0.0, 0.0, 1.0), 1.0, material);
+        let direccion = Vec3::new(0.0, 0.0, -1.0);
+
+        let centro = disco.ray_intersect(&Vec3::new(0.0, 0.0, 3.0), &direccion);
+        let borde = disco.ray_intersect(&Vec3::new(0.9, 0.0, 3.0), &direccion);
+        let fuera = disco.ray_intersect(&Vec3::new(0.8, 0.8, 3.0), &direccion);
+
+        assert!(centro.is_intersecting && borde.is_intersecting);
+        assert!(!fuera.is_intersecting);
+        assert!((centro.u - 0.5).abs() < 1e-5 && (centro.v - 0.5).abs() < 1e-5);
+        let caja = disco.aabb();
+        assert!((caja.max - Vec3::new(1.0, 1.0, 0.0)).magnitude() < 1e-5);
+    }
+}
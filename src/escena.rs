@@ -0,0 +1,1426 @@
+use crate::aabb::Aabb;
+use crate::bvh::Bvh;
+use crate::camera::{Camera, Proyeccion};
+use crate::color::Color;
+use crate::cone::Cone;
+use crate::cube::{Cara, Cube};
+use crate::cylinder::Cylinder;
+use crate::disk::Disk;
+use crate::fisica::{self, MAX_PASOS_POR_CUADRO, PASO_FISICA};
+use crate::light::Light;
+use crate::material::Material;
+use crate::mesh::Mesh;
+use crate::plane::Plane;
+use crate::ray_intersect::{Intersect, RayIntersect};
+use crate::sphere::Sphere;
+use crate::texturas::{Envoltura, Filtro, TextureManager};
+use crate::torus::Torus;
+use crate::transform::Transform;
+use nalgebra_glm::Vec3;
+use serde::Deserialize;
+use std::any::Any;
+use std::collections::HashMap;
+use std::f32::consts::PI;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+// Formato del archivo de escena (JSON). Los nombres de los campos son los que se escriben en el archivo.
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ArchivoEscena {
+    camara: CamaraDesc,
+    fondo: FondoDesc,
+    #[serde(default)]
+    texturas: HashMap<String, String>,
+    materiales: HashMap<String, MaterialDesc>,
+    #[serde(default)]
+    luces: Vec<LuzDesc>,
+    objetos: Vec<ObjetoDesc>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CamaraDesc {
+    ojo: [f32; 3],
+    centro: [f32; 3],
+    #[serde(default = "arriba_por_defecto")]
+    arriba: [f32; 3],
+    #[serde(default)]
+    proyeccion: ProyeccionDesc,
+    // Campo de visión vertical en grados
+    #[serde(default = "fov_por_defecto")]
+    fov: f32,
+    #[serde(default = "alto_ortografico_por_defecto")]
+    alto_ortografico: f32,
+    aspecto: Option<f32>,
+    // Radio de la lente; 0 deja toda la escena enfocada
+    #[serde(default)]
+    apertura: f32,
+    // Por defecto se enfoca el punto `centro`
+    distancia_focal: Option<f32>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+enum ProyeccionDesc {
+    #[default]
+    Perspectiva,
+    Ortografica,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FondoDesc {
+    dia: [u8; 3],
+    noche: Option<[u8; 3]>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MaterialDesc {
+    #[serde(default = "blanco")]
+    difuso: [u8; 3],
+    #[serde(default)]
+    especular: f32,
+    #[serde(default)]
+    albedo: [f32; 2],
+    textura: Option<String>,
+    #[serde(default)]
+    filtro: FiltroDesc,
+    #[serde(default)]
+    envoltura: EnvolturaDesc,
+    #[serde(default = "escala_uv_unitaria")]
+    escala_uv: [f32; 2],
+    #[serde(default)]
+    desplazamiento_uv: [f32; 2],
+    // Nombres de texturas declaradas en "texturas", igual que `textura`
+    mapa_normales: Option<String>,
+    mapa_relieve: Option<String>,
+    #[serde(default = "uno")]
+    intensidad_relieve: f32,
+    #[serde(default)]
+    emisivo: bool,
+    #[serde(default)]
+    reflectividad: f32,
+    #[serde(default)]
+    transparencia: f32,
+    #[serde(default = "uno")]
+    indice_refraccion: f32,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+enum FiltroDesc {
+    #[default]
+    Cercano,
+    Bilineal,
+    Trilineal,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+enum EnvolturaDesc {
+    #[default]
+    Recortar,
+    Repetir,
+    RepetirEspejo,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum TipoLuzDesc {
+    Puntual,
+    Direccional,
+    Foco,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LuzDesc {
+    tipo: TipoLuzDesc,
+    posicion: Option<[f32; 3]>,
+    direccion: Option<[f32; 3]>,
+    #[serde(default = "blanco")]
+    color: [u8; 3],
+    #[serde(default = "uno")]
+    intensidad: f32,
+    #[serde(default)]
+    radio: f32,
+    muestras_sombra: Option<u32>,
+    angulo_interno: Option<f32>,
+    angulo_externo: Option<f32>,
+    // Material de la esfera visible que se dibuja en la posición de la luz (sol, luna)
+    esfera: Option<String>,
+    orbita: Option<Orbita>,
+    #[serde(default)]
+    solo_de_dia: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "tipo", rename_all = "snake_case", deny_unknown_fields)]
+enum ObjetoDesc {
+    Esfera {
+        centro: [f32; 3],
+        radio: f32,
+        material: String,
+    },
+    Cubo {
+        centro: [f32; 3],
+        tamano: f32,
+        material: String,
+        #[serde(default)]
+        velocidad: [f32; 3],
+        // Los cubos dinámicos caen y chocan con los demás cubos; los otros no se mueven
+        #[serde(default)]
+        dinamico: bool,
+        restitucion: Option<f32>,
+        // Materiales de caras sueltas; las que no se nombran usan `material`
+        #[serde(default)]
+        caras: CarasDesc,
+    },
+    // Plano infinito; la textura se repite cada `tamano_textura` unidades
+    Plano {
+        punto: [f32; 3],
+        normal: [f32; 3],
+        material: String,
+        #[serde(default = "uno")]
+        tamano_textura: f32,
+    },
+    Disco {
+        centro: [f32; 3],
+        normal: [f32; 3],
+        radio: f32,
+        material: String,
+    },
+    // Vertical y centrado en `centro`
+    Cilindro {
+        centro: [f32; 3],
+        radio: f32,
+        altura: f32,
+        material: String,
+    },
+    // Vertical, con la base centrada en `centro`
+    Cono {
+        centro: [f32; 3],
+        radio: f32,
+        altura: f32,
+        material: String,
+    },
+    // Acostado en el plano XZ
+    Toro {
+        centro: [f32; 3],
+        radio_mayor: f32,
+        radio_menor: f32,
+        material: String,
+    },
+    // Modelo Wavefront OBJ; la ruta es relativa a la carpeta de la escena
+    Malla {
+        archivo: String,
+        material: String,
+    },
+    // Otro objeto trasladado, rotado (grados sobre X, luego Y, luego Z) y escalado
+    Transformado {
+        objeto: Box<ObjetoDesc>,
+        #[serde(default)]
+        traslacion: [f32; 3],
+        #[serde(default)]
+        rotacion: [f32; 3],
+        #[serde(default = "escala_unitaria")]
+        escala: [f32; 3],
+    },
+}
+
+// Materiales por cara de un cubo. `lados` cubre las cuatro caras verticales; una cara lateral
+// nombrada por separado tiene prioridad sobre `lados`.
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+struct CarasDesc {
+    arriba: Option<String>,
+    abajo: Option<String>,
+    lados: Option<String>,
+    // +X, -X, +Z y -Z
+    derecha: Option<String>,
+    izquierda: Option<String>,
+    frente: Option<String>,
+    atras: Option<String>,
+}
+
+impl CarasDesc {
+    fn por_cara<'a>(&'a self) -> [(Cara, Option<&'a String>); 6] {
+        let lado = |cara: Option<&'a String>| cara.or(self.lados.as_ref());
+        [
+            (Cara::Derecha, lado(self.derecha.as_ref())),
+            (Cara::Izquierda, lado(self.izquierda.as_ref())),
+            (Cara::Arriba, self.arriba.as_ref()),
+            (Cara::Abajo, self.abajo.as_ref()),
+            (Cara::Frente, lado(self.frente.as_ref())),
+            (Cara::Atras, lado(self.atras.as_ref())),
+        ]
+    }
+}
+
+impl ObjetoDesc {
+    fn material(&self) -> &str {
+        match self {
+            ObjetoDesc::Esfera { material, .. }
+            | ObjetoDesc::Cubo { material, .. }
+            | ObjetoDesc::Plano { material, .. }
+            | ObjetoDesc::Disco { material, .. }
+            | ObjetoDesc::Cilindro { material, .. }
+            | ObjetoDesc::Cono { material, .. }
+            | ObjetoDesc::Toro { material, .. }
+            | ObjetoDesc::Malla { material, .. } => material,
+            ObjetoDesc::Transformado { objeto, .. } => objeto.material(),
+        }
+    }
+}
+
+fn arriba_por_defecto() -> [f32; 3] {
+    [0.0, 1.0, 0.0]
+}
+
+fn fov_por_defecto() -> f32 {
+    60.0
+}
+
+fn alto_ortografico_por_defecto() -> f32 {
+    5.0
+}
+
+fn escala_unitaria() -> [f32; 3] {
+    [1.0, 1.0, 1.0]
+}
+
+fn escala_uv_unitaria() -> [f32; 2] {
+    [1.0, 1.0]
+}
+
+fn blanco() -> [u8; 3] {
+    [255, 255, 255]
+}
+
+fn uno() -> f32 {
+    1.0
+}
+
+fn vector(v: [f32; 3]) -> Vec3 {
+    Vec3::new(v[0], v[1], v[2])
+}
+
+fn color(c: [u8; 3]) -> Color {
+    Color::new(c[0], c[1], c[2])
+}
+
+#[derive(Debug)]
+pub enum ErrorEscena {
+    Lectura {
+        ruta: PathBuf,
+        error: std::io::Error,
+    },
+    Formato(serde_json::Error),
+    Textura {
+        nombre: String,
+        ruta: PathBuf,
+        error: image::ImageError,
+    },
+    Malla {
+        ruta: PathBuf,
+        error: tobj::LoadError,
+    },
+    TexturaDesconocida {
+        material: String,
+        textura: String,
+    },
+    MaterialDesconocido {
+        usado_en: String,
+        material: String,
+    },
+    Invalido(String),
+}
+
+impl fmt::Display for ErrorEscena {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ErrorEscena::Lectura { ruta, error } => {
+                write!(f, "no se pudo leer {}: {}", ruta.display(), error)
+            }
+            ErrorEscena::Formato(error) => write!(f, "formato de escena inválido: {}", error),
+            ErrorEscena::Textura {
+                nombre,
+                ruta,
+                error,
+            } => write!(
+                f,
+                "no se pudo cargar la textura \"{}\" desde {}: {}",
+                nombre,
+                ruta.display(),
+                error
+            ),
+            ErrorEscena::Malla { ruta, error } => {
+                write!(
+                    f,
+                    "no se pudo cargar la malla {}: {}",
+                    ruta.display(),
+                    error
+                )
+            }
+            ErrorEscena::TexturaDesconocida { material, textura } => write!(
+                f,
+                "el material \"{}\" usa la textura \"{}\", que no está declarada en \"texturas\"",
+                material, textura
+            ),
+            ErrorEscena::MaterialDesconocido { usado_en, material } => write!(
+                f,
+                "{} usa el material \"{}\", que no está declarado en \"materiales\"",
+                usado_en, material
+            ),
+            ErrorEscena::Invalido(mensaje) => write!(f, "{}", mensaje),
+        }
+    }
+}
+
+impl std::error::Error for ErrorEscena {}
+
+// Movimiento circular de una luz alrededor del eje Y, conservando su altura
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Orbita {
+    pub radio: f32,
+    // Segundos que tarda en dar una vuelta completa
+    pub periodo: f32,
+    #[serde(default)]
+    pub fase: f32,
+}
+
+impl Orbita {
+    pub fn posicion(&self, altura: f32, tiempo: f32) -> Vec3 {
+        let angulo = (self.fase + tiempo * 2.0 * PI / self.periodo) % (2.0 * PI);
+        Vec3::new(self.radio * angulo.cos(), altura, self.radio * angulo.sin())
+    }
+}
+
+// Identificadores estables de los elementos de una escena; no cambian al agregar otros
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ObjetoId(usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LuzId(usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MaterialId(usize);
+
+struct LuzEscena {
+    luz: Light,
+    // Esfera visible que acompaña a la luz (sol, luna); su radio es el de la luz de área
+    esfera: Option<Sphere>,
+    orbita: Option<Orbita>,
+    solo_de_dia: bool,
+}
+
+// Escena persistente: se construye una vez y entre cuadros solo se modifica lo que cambia
+pub struct Escena {
+    pub camera: Camera,
+    pub fondo_dia: Color,
+    pub fondo_noche: Color,
+    // De noche se apagan las luces marcadas como solo_de_dia y se ocultan sus esferas
+    pub es_dia: bool,
+    materiales: Vec<Material>,
+    nombres_materiales: HashMap<String, MaterialId>,
+    luces: Vec<LuzEscena>,
+    objetos: Bvh,
+    dinamicos: Vec<ObjetoId>,
+    // Tiempo que todavía no alcanza para un paso completo de la física
+    acumulador_fisica: f32,
+    // Hay objetos que todavía no están en el BVH
+    bvh_incompleto: bool,
+    // Algún objeto se movió y las cajas del BVH ya no lo envuelven
+    bvh_desactualizado: bool,
+}
+
+impl Escena {
+    pub fn new(camera: Camera, fondo_dia: Color, fondo_noche: Color) -> Self {
+        Escena {
+            camera,
+            fondo_dia,
+            fondo_noche,
+            es_dia: true,
+            materiales: Vec::new(),
+            nombres_materiales: HashMap::new(),
+            luces: Vec::new(),
+            objetos: Bvh::new(Vec::new()),
+            dinamicos: Vec::new(),
+            acumulador_fisica: 0.0,
+            bvh_incompleto: false,
+            bvh_desactualizado: false,
+        }
+    }
+
+    // Las rutas de las texturas se resuelven respecto a la carpeta del archivo de escena
+    pub fn cargar(ruta: &Path) -> Result<Escena, ErrorEscena> {
+        let texto = std::fs::read_to_string(ruta).map_err(|error| ErrorEscena::Lectura {
+            ruta: ruta.to_path_buf(),
+            error,
+        })?;
+        let carpeta = ruta.parent().unwrap_or_else(|| Path::new("."));
+        Escena::desde_json(&texto, carpeta)
+    }
+
+    pub fn desde_json(texto: &str, carpeta: &Path) -> Result<Escena, ErrorEscena> {
+        let archivo: ArchivoEscena = serde_json::from_str(texto).map_err(ErrorEscena::Formato)?;
+
+        let fondo_dia = color(archivo.fondo.dia);
+        let mut escena = Escena::new(
+            construir_camara(&archivo.camara)?,
+            fondo_dia,
+            archivo.fondo.noche.map(color).unwrap_or(fondo_dia),
+        );
+
+        let mut manejador_textura = TextureManager::new();
+        for (nombre, ruta_relativa) in &archivo.texturas {
+            let ruta = carpeta.join(ruta_relativa);
+            let imagen = image::open(&ruta)
+                .map_err(|error| ErrorEscena::Textura {
+                    nombre: nombre.clone(),
+                    ruta: ruta.clone(),
+                    error,
+                })?
+                .into_rgba8();
+            manejador_textura.cargar_textura(nombre, imagen);
+        }
+
+        for (nombre, desc) in &archivo.materiales {
+            let buscar_textura = |textura: &Option<String>| match textura {
+                Some(textura) => {
+                    manejador_textura
+                        .get_textura(textura)
+                        .map(Some)
+                        .ok_or_else(|| ErrorEscena::TexturaDesconocida {
+                            material: nombre.clone(),
+                            textura: textura.clone(),
+                        })
+                }
+                None => Ok(None),
+            };
+            let textura = buscar_textura(&desc.textura)?;
+            let material = Material {
+                filtro: match desc.filtro {
+                    FiltroDesc::Cercano => Filtro::Cercano,
+                    FiltroDesc::Bilineal => Filtro::Bilineal,
+                    FiltroDesc::Trilineal => Filtro::Trilineal,
+                },
+                envoltura: match desc.envoltura {
+                    EnvolturaDesc::Recortar => Envoltura::Recortar,
+                    EnvolturaDesc::Repetir => Envoltura::Repetir,
+                    EnvolturaDesc::RepetirEspejo => Envoltura::RepetirEspejo,
+                },
+                escala_uv: desc.escala_uv,
+                desplazamiento_uv: desc.desplazamiento_uv,
+                mapa_normales: buscar_textura(&desc.mapa_normales)?,
+                mapa_relieve: buscar_textura(&desc.mapa_relieve)?,
+                intensidad_relieve: desc.intensidad_relieve,
+                emisivo: desc.emisivo,
+                reflectividad: desc.reflectividad,
+                transparencia: desc.transparencia,
+                indice_refraccion: desc.indice_refraccion,
+                ..Material::new(color(desc.difuso), desc.especular, desc.albedo, textura)
+            };
+            escena.agregar_material(nombre, material);
+        }
+
+        let buscar_material = |escena: &Escena, usado_en: String, nombre: &str| {
+            escena
+                .material_id(nombre)
+                .map(|id| escena.material(id).clone())
+                .ok_or_else(|| ErrorEscena::MaterialDesconocido {
+                    usado_en,
+                    material: nombre.to_string(),
+                })
+        };
+
+        for (i, desc) in archivo.luces.iter().enumerate() {
+            let luz = construir_luz(i, desc)?;
+            let esfera = match &desc.esfera {
+                Some(nombre) => Some(buscar_material(&escena, format!("la luz {}", i), nombre)?),
+                None => None,
+            };
+            escena.agregar_luz(luz, esfera, desc.orbita.clone(), desc.solo_de_dia);
+        }
+
+        for (i, desc) in archivo.objetos.iter().enumerate() {
+            let buscar =
+                |nombre: &str| buscar_material(&escena, format!("el objeto {}", i), nombre);
+            let objeto = construir_objeto(i, desc, &buscar, carpeta)?;
+            let id = escena.agregar_objeto(objeto);
+            // Los cubos dinámicos caen y chocan con los demás cubos en cada `avanzar_fisica`
+            if let ObjetoDesc::Cubo { dinamico: true, .. } = desc {
+                escena.dinamicos.push(id);
+            }
+        }
+        escena.preparar_cuadro();
+
+        Ok(escena)
+    }
+
+    pub fn agregar_material(&mut self, nombre: &str, material: Material) -> MaterialId {
+        let id = MaterialId(self.materiales.len());
+        self.materiales.push(material);
+        self.nombres_materiales.insert(nombre.to_string(), id);
+        id
+    }
+
+    pub fn material_id(&self, nombre: &str) -> Option<MaterialId> {
+        self.nombres_materiales.get(nombre).copied()
+    }
+
+    pub fn material(&self, id: MaterialId) -> &Material {
+        &self.materiales[id.0]
+    }
+
+    // El BVH se reconstruye en el siguiente `preparar_cuadro`; para mover el objeto después
+    // basta con `objeto_mut`, que solo reajusta las cajas
+    pub fn agregar_objeto(&mut self, objeto: Box<dyn RayIntersect>) -> ObjetoId {
+        self.bvh_incompleto = true;
+        ObjetoId(self.objetos.agregar(objeto))
+    }
+
+    // Devuelve el objeto si es del tipo pedido; el BVH se reajusta en el siguiente `preparar_cuadro`
+    pub fn objeto_mut<T: RayIntersect>(&mut self, id: ObjetoId) -> Option<&mut T> {
+        self.bvh_desactualizado = true;
+        let objeto: &mut dyn Any = self.objetos.objeto_mut(id.0);
+        objeto.downcast_mut::<T>()
+    }
+
+    pub fn agregar_luz(
+        &mut self,
+        luz: Light,
+        esfera: Option<Material>,
+        orbita: Option<Orbita>,
+        solo_de_dia: bool,
+    ) -> LuzId {
+        let esfera = esfera.map(|material| Sphere {
+            center: luz.position,
+            radius: luz.radio_de_luz,
+            material,
+        });
+        self.luces.push(LuzEscena {
+            luz,
+            esfera,
+            orbita,
+            solo_de_dia,
+        });
+        LuzId(self.luces.len() - 1)
+    }
+
+    // Las luces no están en el BVH: la esfera que acompaña a la luz se acomoda a su posición y
+    // radio en el siguiente `preparar_cuadro`
+    pub fn luz_mut(&mut self, id: LuzId) -> &mut Light {
+        &mut self.luces[id.0].luz
+    }
+
+    // Mueve las luces que tienen órbita a su posición en el instante `tiempo` (segundos)
+    pub fn actualizar_luces(&mut self, tiempo: f32) {
+        for i in 0..self.luces.len() {
+            if let Some(orbita) = self.luces[i].orbita.clone() {
+                let luz = self.luz_mut(LuzId(i));
+                luz.position = orbita.posicion(luz.position.y, tiempo);
+            }
+        }
+    }
+
+    // Acumula `delta_tiempo` y avanza la simulación en pasos fijos de PASO_FISICA. Cada objeto
+    // que no es dinámico sirve de obstáculo con su caja envolvente; los planos, que no tienen
+    // una caja finita, con el semiespacio que hay detrás si están alineados a los ejes.
+    pub fn avanzar_fisica(&mut self, delta_tiempo: f32) {
+        let limite = PASO_FISICA * MAX_PASOS_POR_CUADRO as f32;
+        self.acumulador_fisica = (self.acumulador_fisica + delta_tiempo).min(limite);
+        if self.dinamicos.is_empty() || self.acumulador_fisica < PASO_FISICA {
+            return;
+        }
+
+        // La simulación mueve copias de los cubos dinámicos, que al terminar vuelven a la escena
+        // por su identificador
+        let mut cubos = Vec::new();
+        let mut estaticos = Vec::new();
+        for (i, objeto) in self.objetos.objetos_mut().iter_mut().enumerate() {
+            let caja = objeto.aabb();
+            let objeto: &mut dyn Any = objeto.as_mut();
+            if self.dinamicos.contains(&ObjetoId(i)) {
+                cubos.extend(objeto.downcast_ref::<Cube>().cloned());
+            } else if let Some(plano) = objeto.downcast_ref::<Plane>() {
+                estaticos.extend(plano.semiespacio());
+            } else if caja.es_finita() {
+                estaticos.push(caja);
+            }
+        }
+
+        let mut referencias: Vec<&mut Cube> = cubos.iter_mut().collect();
+        while self.acumulador_fisica >= PASO_FISICA {
+            fisica::paso(&mut referencias, &estaticos, PASO_FISICA);
+            self.acumulador_fisica -= PASO_FISICA;
+        }
+        for (id, cubo) in self.dinamicos.clone().into_iter().zip(cubos) {
+            if let Some(destino) = self.objeto_mut::<Cube>(id) {
+                *destino = cubo;
+            }
+        }
+    }
+
+    // Deja la escena lista para lanzar rayos: acomoda las esferas de las luces y pone al día
+    // el BVH si se agregaron o movieron objetos
+    pub fn preparar_cuadro(&mut self) {
+        for luz in &mut self.luces {
+            if let Some(esfera) = &mut luz.esfera {
+                esfera.center = luz.luz.position;
+                esfera.radius = luz.luz.radio_de_luz;
+            }
+        }
+        if self.bvh_incompleto {
+            self.objetos.reconstruir();
+        } else if self.bvh_desactualizado {
+            self.objetos.reajustar();
+        }
+        self.bvh_incompleto = false;
+        self.bvh_desactualizado = false;
+    }
+
+    pub fn fondo(&self) -> Color {
+        if self.es_dia {
+            self.fondo_dia
+        } else {
+            self.fondo_noche
+        }
+    }
+
+    fn luces_encendidas(&self) -> impl Iterator<Item = &LuzEscena> {
+        self.luces
+            .iter()
+            .filter(move |luz| self.es_dia || !luz.solo_de_dia)
+    }
+
+    pub fn luces_activas(&self) -> Vec<Light> {
+        self.luces_encendidas().map(|luz| luz.luz.clone()).collect()
+    }
+}
+
+impl RayIntersect for Escena {
+    fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Intersect {
+        debug_assert!(
+            !self.bvh_desactualizado && !self.bvh_incompleto,
+            "falta llamar a preparar_cuadro después de mover objetos"
+        );
+
+        let mut intersect = self.objetos.ray_intersect(ray_origin, ray_direction);
+        for luz in self.luces_encendidas() {
+            if let Some(esfera) = &luz.esfera {
+                let tmp = esfera.ray_intersect(ray_origin, ray_direction);
+                if tmp.is_intersecting
+                    && (!intersect.is_intersecting || tmp.distance < intersect.distance)
+                {
+                    intersect = tmp;
+                }
+            }
+        }
+        intersect
+    }
+
+    fn get_uv(&self, _point: &Vec3) -> (f32, f32) {
+        (0.0, 0.0)
+    }
+
+    fn aabb(&self) -> Aabb {
+        self.luces_encendidas()
+            .filter_map(|luz| luz.esfera.as_ref())
+            .fold(self.objetos.aabb(), |caja, esfera| {
+                caja.unir(&esfera.aabb())
+            })
+    }
+}
+
+fn construir_camara(desc: &CamaraDesc) -> Result<Camera, ErrorEscena> {
+    if !(desc.fov > 0.0 && desc.fov < 180.0) {
+        return Err(ErrorEscena::Invalido(format!(
+            "el fov de la cámara es {}, debe estar entre 0 y 180 grados",
+            desc.fov
+        )));
+    }
+    if desc.apertura < 0.0
+        || desc
+            .distancia_focal
+            .is_some_and(|distancia| distancia <= 0.0)
+    {
+        return Err(ErrorEscena::Invalido(
+            "la apertura de la cámara no puede ser negativa y la distancia focal debe ser positiva"
+                .to_string(),
+        ));
+    }
+    if desc.alto_ortografico <= 0.0 || desc.aspecto.is_some_and(|aspecto| aspecto <= 0.0) {
+        return Err(ErrorEscena::Invalido(
+            "el alto ortográfico y el aspecto de la cámara deben ser positivos".to_string(),
+        ));
+    }
+
+    let mut camara = Camera::new(vector(desc.ojo), vector(desc.centro), vector(desc.arriba));
+    camara.proyeccion = match desc.proyeccion {
+        ProyeccionDesc::Perspectiva => Proyeccion::Perspectiva,
+        ProyeccionDesc::Ortografica => Proyeccion::Ortografica,
+    };
+    camara.fov = desc.fov.to_radians();
+    camara.alto_ortografico = desc.alto_ortografico;
+    camara.aspecto = desc.aspecto;
+    camara.apertura = desc.apertura;
+    if let Some(distancia) = desc.distancia_focal {
+        camara.distancia_focal = distancia;
+    }
+    Ok(camara)
+}
+
+// `buscar_material` traduce los nombres de materiales del archivo
+fn construir_objeto(
+    i: usize,
+    desc: &ObjetoDesc,
+    buscar_material: &dyn Fn(&str) -> Result<Material, ErrorEscena>,
+    carpeta: &Path,
+) -> Result<Box<dyn RayIntersect>, ErrorEscena> {
+    let material = buscar_material(desc.material())?;
+    let positivo = |campo: &str, valor: f32| {
+        if valor > 0.0 {
+            Ok(valor)
+        } else {
+            Err(ErrorEscena::Invalido(format!(
+                "el objeto {} tiene {} {}, debe ser positivo",
+                i, campo, valor
+            )))
+        }
+    };
+    let direccion = |campo: &str, valor: [f32; 3]| {
+        let v = vector(valor);
+        if v.magnitude() > 0.0 {
+            Ok(v)
+        } else {
+            Err(ErrorEscena::Invalido(format!(
+                "el objeto {} tiene {} nula",
+                i, campo
+            )))
+        }
+    };
+
+    let objeto: Box<dyn RayIntersect> = match desc {
+        ObjetoDesc::Esfera { centro, radio, .. } => Box::new(Sphere {
+            center: vector(*centro),
+            radius: positivo("radio", *radio)?,
+            material,
+        }),
+        ObjetoDesc::Cubo {
+            centro,
+            tamano,
+            velocidad,
+            restitucion,
+            caras,
+            ..
+        } => {
+            let mut cubo = Cube::new(
+                vector(*centro),
+                positivo("tamano", *tamano)?,
+                material,
+                vector(*velocidad),
+            );
+            if let Some(restitucion) = restitucion {
+                cubo.restitucion = *restitucion;
+            }
+            for (cara, nombre) in caras.por_cara() {
+                if let Some(nombre) = nombre {
+                    cubo.asignar_material_cara(cara, buscar_material(nombre)?);
+                }
+            }
+            Box::new(cubo)
+        }
+        ObjetoDesc::Plano {
+            punto,
+            normal,
+            tamano_textura,
+            ..
+        } => Box::new(Plane::new(
+            vector(*punto),
+            direccion("normal", *normal)?,
+            positivo("tamano_textura", *tamano_textura)?,
+            material,
+        )),
+        ObjetoDesc::Disco {
+            centro,
+            normal,
+            radio,
+            ..
+        } => Box::new(Disk::new(
+            vector(*centro),
+            direccion("normal", *normal)?,
+            positivo("radio", *radio)?,
+            material,
+        )),
+        ObjetoDesc::Cilindro {
+            centro,
+            radio,
+            altura,
+            ..
+        } => Box::new(Cylinder::new(
+            vector(*centro),
+            positivo("radio", *radio)?,
+            positivo("altura", *altura)?,
+            material,
+        )),
+        ObjetoDesc::Cono {
+            centro,
+            radio,
+            altura,
+            ..
+        } => Box::new(Cone::new(
+            vector(*centro),
+            positivo("radio", *radio)?,
+            positivo("altura", *altura)?,
+            material,
+        )),
+        ObjetoDesc::Toro {
+            centro,
+            radio_mayor,
+            radio_menor,
+            ..
+        } => Box::new(Torus::new(
+            vector(*centro),
+            positivo("radio_mayor", *radio_mayor)?,
+            positivo("radio_menor", *radio_menor)?,
+            material,
+        )),
+        ObjetoDesc::Malla { archivo, .. } => {
+            let ruta = carpeta.join(archivo);
+            Box::new(
+                Mesh::cargar_obj(&ruta, material)
+                    .map_err(|error| ErrorEscena::Malla { ruta, error })?,
+            )
+        }
+        ObjetoDesc::Transformado {
+            objeto,
+            traslacion,
+            rotacion,
+            escala,
+        } => {
+            if let ObjetoDesc::Cubo { dinamico: true, .. } = **objeto {
+                return Err(ErrorEscena::Invalido(format!(
+                    "el objeto {} es un cubo dinámico y no puede tener transformación",
+                    i
+                )));
+            }
+            let interno = construir_objeto(i, objeto, buscar_material, carpeta)?;
+            let transformado = Transform::desde_partes(
+                interno,
+                vector(*traslacion),
+                vector(*rotacion),
+                vector(*escala),
+            )
+            .ok_or_else(|| {
+                ErrorEscena::Invalido(format!(
+                    "el objeto {} tiene una escala nula y no se puede invertir",
+                    i
+                ))
+            })?;
+            Box::new(transformado)
+        }
+    };
+    Ok(objeto)
+}
+
+fn construir_luz(i: usize, desc: &LuzDesc) -> Result<Light, ErrorEscena> {
+    let falta = |campo: &str| {
+        ErrorEscena::Invalido(format!("a la luz {} le falta el campo \"{}\"", i, campo))
+    };
+
+    let mut luz = match desc.tipo {
+        TipoLuzDesc::Puntual => Light::new(
+            vector(desc.posicion.ok_or_else(|| falta("posicion"))?),
+            color(desc.color),
+            desc.intensidad,
+            desc.radio,
+        ),
+        TipoLuzDesc::Direccional => Light::direccional(
+            vector(desc.direccion.ok_or_else(|| falta("direccion"))?),
+            color(desc.color),
+            desc.intensidad,
+        ),
+        TipoLuzDesc::Foco => Light::foco(
+            vector(desc.posicion.ok_or_else(|| falta("posicion"))?),
+            vector(desc.direccion.ok_or_else(|| falta("direccion"))?),
+            color(desc.color),
+            desc.intensidad,
+            desc.angulo_interno.ok_or_else(|| falta("angulo_interno"))?,
+            desc.angulo_externo.ok_or_else(|| falta("angulo_externo"))?,
+        ),
+    };
+
+    if let Some(muestras) = desc.muestras_sombra {
+        if muestras == 0 {
+            return Err(ErrorEscena::Invalido(format!(
+                "la luz {} necesita al menos una muestra de sombra",
+                i
+            )));
+        }
+        luz.muestras_sombra = muestras;
+    }
+    if let Some(orbita) = &desc.orbita {
+        if orbita.periodo <= 0.0 {
+            return Err(ErrorEscena::Invalido(format!(
+                "la órbita de la luz {} debe tener un periodo positivo",
+                i
+            )));
+        }
+    }
+
+    Ok(luz)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ESCENA_MINIMA: &str = r#"{
+        "camara": { "ojo": [0, 0, 5], "centro": [0, 0, 0] },
+        "fondo": { "dia": [10, 20, 30] },
+        "materiales": { "rojo": { "difuso": [255, 0, 0], "albedo": [0.9, 0.1] } },
+        "luces": [ { "tipo": "puntual", "posicion": [0, 10, 0] } ],
+        "objetos": [
+            { "tipo": "cubo", "centro": [0, 0, 0], "tamano": 1, "material": "rojo" },
+            { "tipo": "esfera", "centro": [2, 0, 0], "radio": 0.5, "material": "rojo" }
+        ]
+    }"#;
+
+    fn error_de(texto: &str) -> String {
+        match Escena::desde_json(texto, Path::new(".")) {
+            Ok(_) => panic!("la escena debía ser inválida"),
+            Err(error) => error.to_string(),
+        }
+    }
+
+    #[test]
+    fn carga_una_escena_minima() {
+        let escena = Escena::desde_json(ESCENA_MINIMA, Path::new(".")).unwrap();
+
+        assert_eq!(escena.luces_activas().len(), 1);
+        assert_eq!(escena.fondo(), Color::new(10, 20, 30));
+        assert_eq!(escena.camera.arriba, Vec3::new(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn la_camara_puede_ser_ortografica() {
+        let texto = ESCENA_MINIMA.replace(
+            r#""centro": [0, 0, 0] },"#,
+            r#""centro": [0, 0, 0], "proyeccion": "ortografica", "alto_ortografico": 8, "aspecto": 1.5 },"#,
+        );
+
+        let escena = Escena::desde_json(&texto, Path::new(".")).unwrap();
+
+        assert_eq!(escena.camera.proyeccion, Proyeccion::Ortografica);
+        assert_eq!(escena.camera.alto_ortografico, 8.0);
+        assert_eq!(escena.camera.aspecto, Some(1.5));
+        assert!((escena.camera.fov - PI / 3.0).abs() < 1e-6);
+        let fov_invalido = ESCENA_MINIMA.replace(
+            r#""centro": [0, 0, 0] },"#,
+            r#""centro": [0, 0, 0], "fov": 180 },"#,
+        );
+        assert!(error_de(&fov_invalido).contains("fov"));
+    }
+
+    #[test]
+    fn la_camara_enfoca_el_centro_salvo_que_se_indique() {
+        let con_lente = |extra: &str| {
+            let texto = ESCENA_MINIMA.replace(
+                r#""centro": [0, 0, 0] },"#,
+                &format!(r#""centro": [0, 0, 0], "apertura": 0.1{} }},"#, extra),
+            );
+            Escena::desde_json(&texto, Path::new(".")).map(|escena| escena.camera)
+        };
+
+        let al_centro = con_lente("").unwrap();
+        let cercana = con_lente(r#", "distancia_focal": 2"#).unwrap();
+
+        assert_eq!(al_centro.apertura, 0.1);
+        assert_eq!(al_centro.distancia_focal, 5.0);
+        assert_eq!(cercana.distancia_focal, 2.0);
+        assert!(con_lente(r#", "distancia_focal": 0"#).is_err());
+    }
+
+    #[test]
+    fn carga_el_diorama_del_repositorio() {
+        let escena = Escena::cargar(Path::new("escenas/diorama.json")).unwrap();
+
+        assert_eq!(escena.luces_activas().len(), 2);
+        let mut escena = escena;
+        escena.es_dia = false;
+        assert_eq!(escena.luces_activas().len(), 1);
+    }
+
+    #[test]
+    fn el_material_lee_el_muestreo_de_la_textura() {
+        let texto = ESCENA_MINIMA.replace(
+            r#""albedo": [0.9, 0.1] }"#,
+            r#""albedo": [0.9, 0.1], "filtro": "trilineal", "envoltura": "repetir_espejo",
+                "escala_uv": [4, 2], "desplazamiento_uv": [0.5, 0] }"#,
+        );
+        let escena = Escena::desde_json(&texto, Path::new(".")).unwrap();
+        let material = escena.material(escena.material_id("rojo").unwrap());
+
+        assert_eq!(material.filtro, Filtro::Trilineal);
+        assert_eq!(material.envoltura, Envoltura::RepetirEspejo);
+        assert_eq!(material.escala_uv, [4.0, 2.0]);
+        assert_eq!(material.desplazamiento_uv, [0.5, 0.0]);
+
+        let por_defecto = Escena::desde_json(ESCENA_MINIMA, Path::new(".")).unwrap();
+        let material = por_defecto.material(por_defecto.material_id("rojo").unwrap());
+        assert_eq!(material.envoltura, Envoltura::Recortar);
+        assert_eq!(material.escala_uv, [1.0, 1.0]);
+    }
+
+    #[test]
+    fn un_cubo_con_materiales_por_cara() {
+        let texto = ESCENA_MINIMA
+            .replace(
+                r#""materiales": {"#,
+                r#""materiales": { "verde": { "difuso": [0, 255, 0] }, "azul": { "difuso": [0, 0, 255] },"#,
+            )
+            .replace(
+                r#""tamano": 1, "material": "rojo""#,
+                r#""tamano": 1, "material": "rojo",
+                    "caras": { "arriba": "verde", "lados": "azul", "frente": "rojo" }"#,
+            );
+        let escena = Escena::desde_json(&texto, Path::new(".")).unwrap();
+        let difuso = |origen: Vec3, direccion: Vec3| {
+            escena.ray_intersect(&origen, &direccion).material.diffuse
+        };
+
+        let rojo = Color::new(255, 0, 0);
+        let arriba = difuso(Vec3::new(0.0, 5.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        let abajo = difuso(Vec3::new(0.0, -5.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+        let izquierda = difuso(Vec3::new(-5.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        let frente = difuso(Vec3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+
+        assert_eq!(arriba, Color::new(0, 255, 0));
+        assert_eq!(abajo, rojo);
+        assert_eq!(izquierda, Color::new(0, 0, 255));
+        assert_eq!(frente, rojo);
+    }
+
+    #[test]
+    fn material_de_cara_desconocido_es_un_error() {
+        let texto = ESCENA_MINIMA.replace(
+            r#""tamano": 1, "material": "rojo""#,
+            r#""tamano": 1, "material": "rojo", "caras": { "abajo": "lava" }"#,
+        );
+
+        let error = error_de(&texto);
+
+        assert!(
+            error.contains("lava") && error.contains("objeto 0"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn mover_un_objeto_por_su_identificador() {
+        let mut escena = Escena::desde_json(ESCENA_MINIMA, Path::new(".")).unwrap();
+        let material = escena.material(escena.material_id("rojo").unwrap()).clone();
+        let id = escena.agregar_objeto(Box::new(Cube::new(
+            Vec3::new(0.0, 3.0, 0.0),
+            1.0,
+            material,
+            Vec3::zeros(),
+        )));
+        escena.preparar_cuadro();
+        let origen = Vec3::new(5.0, 3.0, 0.0);
+        let direccion = Vec3::new(-1.0, 0.0, 0.0);
+        assert!(escena.ray_intersect(&origen, &direccion).is_intersecting);
+
+        escena.objeto_mut::<Cube>(id).unwrap().center.y = 6.0;
+        escena.preparar_cuadro();
+
+        assert!(!escena.ray_intersect(&origen, &direccion).is_intersecting);
+        let movido = Vec3::new(5.0, 6.0, 0.0);
+        assert!(escena.ray_intersect(&movido, &direccion).is_intersecting);
+        assert!(escena.objeto_mut::<Sphere>(id).is_none());
+    }
+
+    #[test]
+    fn la_esfera_de_la_luz_sigue_a_la_luz() {
+        let mut escena = Escena::desde_json(ESCENA_MINIMA, Path::new(".")).unwrap();
+        let material = Material::emisivo(Color::new(255, 255, 0));
+        let orbita = Orbita {
+            radio: 10.0,
+            periodo: 4.0,
+            fase: 0.0,
+        };
+        let id = escena.agregar_luz(
+            Light::new(
+                Vec3::new(0.0, 20.0, 0.0),
+                Color::new(255, 255, 255),
+                1.0,
+                1.0,
+            ),
+            Some(material),
+            Some(orbita),
+            true,
+        );
+        escena.preparar_cuadro();
+        let abajo = Vec3::new(0.0, -1.0, 0.0);
+        assert!(
+            escena
+                .ray_intersect(&Vec3::new(0.0, 30.0, 0.0), &abajo)
+                .material
+                .emisivo
+        );
+
+        // A los cero segundos la órbita la lleva a x = 10
+        escena.actualizar_luces(0.0);
+        escena.preparar_cuadro();
+
+        assert!(
+            !escena
+                .ray_intersect(&Vec3::new(0.0, 30.0, 0.0), &abajo)
+                .material
+                .emisivo
+        );
+        assert!(
+            escena
+                .ray_intersect(&Vec3::new(10.0, 30.0, 0.0), &abajo)
+                .material
+                .emisivo
+        );
+
+        // Moverla por su identificador también arrastra la esfera
+        escena.luz_mut(id).position = Vec3::new(-10.0, 20.0, 0.0);
+        escena.preparar_cuadro();
+        assert!(
+            escena
+                .ray_intersect(&Vec3::new(-10.0, 30.0, 0.0), &abajo)
+                .material
+                .emisivo
+        );
+
+        escena.es_dia = false;
+        assert!(
+            !escena
+                .ray_intersect(&Vec3::new(-10.0, 30.0, 0.0), &abajo)
+                .material
+                .emisivo
+        );
+    }
+
+    #[test]
+    fn la_fisica_avanza_en_pasos_fijos() {
+        let texto = ESCENA_MINIMA.replace(
+            r#""tamano": 1, "material": "rojo""#,
+            r#""tamano": 1, "material": "rojo", "dinamico": true"#,
+        );
+        let mut escena = Escena::desde_json(&texto, Path::new(".")).unwrap();
+        let abajo = Vec3::new(0.0, -1.0, 0.0);
+        let altura = |escena: &Escena| {
+            let arriba = Vec3::new(0.0, 10.0, 0.0);
+            10.0 - escena.ray_intersect(&arriba, &abajo).distance
+        };
+
+        // Medio paso no alcanza para mover nada
+        escena.avanzar_fisica(PASO_FISICA / 2.0);
+        escena.preparar_cuadro();
+        assert_eq!(altura(&escena), 0.5);
+
+        escena.avanzar_fisica(PASO_FISICA / 2.0);
+        escena.preparar_cuadro();
+        assert!(altura(&escena) < 0.5);
+    }
+
+    #[test]
+    fn los_cubos_del_diorama_se_asientan() {
+        let mut escena = Escena::cargar(Path::new("escenas/diorama.json")).unwrap();
+
+        for _ in 0..300 {
+            escena.avanzar_fisica(1.0 / 60.0);
+        }
+
+        for id in escena.dinamicos.clone() {
+            let cubo = escena.objeto_mut::<Cube>(id).unwrap();
+            assert!(cubo.velocidad.magnitude() < 0.05, "{:?}", cubo.velocidad);
+            // Ninguno atraviesa el suelo, cuya cara superior está en y = -10
+            assert!(
+                cubo.center.y - cubo.size / 2.0 > -10.01,
+                "{}",
+                cubo.center.y
+            );
+        }
+    }
+
+    #[test]
+    fn un_cubo_cae_sobre_un_cubo_transformado() {
+        let texto = ESCENA_MINIMA.replace(
+            r#"{ "tipo": "esfera", "centro": [2, 0, 0], "radio": 0.5, "material": "rojo" }"#,
+            r#"{ "tipo": "transformado", "traslacion": [0, -3, 0], "escala": [2, 2, 2],
+                 "objeto": { "tipo": "cubo", "centro": [0, 0, 0], "tamano": 1, "material": "rojo" } }"#,
+        );
+        let texto = texto.replace(
+            r#""tamano": 1, "material": "rojo" },"#,
+            r#""tamano": 1, "material": "rojo", "dinamico": true },"#,
+        );
+        let mut escena = Escena::desde_json(&texto, Path::new(".")).unwrap();
+
+        for _ in 0..300 {
+            escena.avanzar_fisica(1.0 / 60.0);
+        }
+
+        // El cubo transformado ocupa de y = -4 a y = -2: el dinámico queda apoyado encima
+        let id = escena.dinamicos[0];
+        let cubo = escena.objeto_mut::<Cube>(id).unwrap();
+        assert!((cubo.center.y - -1.5).abs() < 0.01, "{}", cubo.center.y);
+    }
+
+    #[test]
+    fn error_de_sintaxis_indica_la_linea() {
+        let error = error_de("{\n  \"camara\": {\n    \"ojo\": [0, 0,\n}");
+
+        assert!(error.contains("line 4"), "{}", error);
+    }
+
+    #[test]
+    fn material_desconocido_nombra_al_objeto() {
+        let texto = ESCENA_MINIMA.replace(
+            r#""radio": 0.5, "material": "rojo""#,
+            r#""radio": 0.5, "material": "azul""#,
+        );
+
+        let error = error_de(&texto);
+
+        assert!(error.contains("objeto 1"), "{}", error);
+        assert!(error.contains("\"azul\""), "{}", error);
+    }
+
+    #[test]
+    fn campo_desconocido_es_un_error() {
+        let texto = ESCENA_MINIMA.replace("\"tamano\": 1", "\"tamaño\": 1");
+
+        let error = error_de(&texto);
+
+        assert!(error.contains("tamaño"), "{}", error);
+    }
+
+    #[test]
+    fn luz_direccional_requiere_direccion() {
+        let texto = ESCENA_MINIMA.replace(
+            r#"{ "tipo": "puntual", "posicion": [0, 10, 0] }"#,
+            r#"{ "tipo": "direccional" }"#,
+        );
+
+        let error = error_de(&texto);
+
+        assert!(error.contains("direccion"), "{}", error);
+    }
+
+    #[test]
+    fn textura_inexistente_muestra_la_ruta() {
+        let texto = ESCENA_MINIMA.replace(
+            r#""materiales""#,
+            r#""texturas": { "falta": "no_existe.png" }, "materiales""#,
+        );
+
+        let error = error_de(&texto);
+
+        assert!(error.contains("no_existe.png"), "{}", error);
+    }
+
+    #[test]
+    fn carga_todas_las_primitivas() {
+        let texto = ESCENA_MINIMA.replace(
+            r#"{ "tipo": "esfera", "centro": [2, 0, 0], "radio": 0.5, "material": "rojo" }"#,
+            r#"{ "tipo": "plano", "punto": [0, -1, 0], "normal": [0, 1, 0], "material": "rojo" },
+            { "tipo": "disco", "centro": [0, 0, -2], "normal": [0, 0, 1], "radio": 1, "material": "rojo" },
+            { "tipo": "cilindro", "centro": [2, 0, 0], "radio": 0.5, "altura": 1, "material": "rojo" },
+            { "tipo": "cono", "centro": [-2, 0, 0], "radio": 0.5, "altura": 1, "material": "rojo" },
+            { "tipo": "toro", "centro": [0, 2, 0], "radio_mayor": 1, "radio_menor": 0.2, "material": "rojo" }"#,
+        );
+
+        let escena = Escena::desde_json(&texto, Path::new(".")).unwrap();
+
+        // El plano no tiene límites, así que la escena tampoco
+        assert!(!escena.aabb().es_finita());
+        // Sobre el tubo del toro
+        let hit = escena.ray_intersect(&Vec3::new(1.0, 5.0, 0.0), &Vec3::new(0.0, -1.0, 0.0));
+        assert!((hit.distance - 2.8).abs() < 1e-4, "{}", hit.distance);
+    }
+
+    #[test]
+    fn normal_nula_es_un_error() {
+        let texto = ESCENA_MINIMA.replace(
+            r#"{ "tipo": "esfera", "centro": [2, 0, 0], "radio": 0.5, "material": "rojo" }"#,
+            r#"{ "tipo": "plano", "punto": [0, 0, 0], "normal": [0, 0, 0], "material": "rojo" }"#,
+        );
+
+        let error = error_de(&texto);
+
+        assert!(
+            error.contains("objeto 1") && error.contains("normal"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn objeto_transformado() {
+        let texto = ESCENA_MINIMA.replace(
+            r#"{ "tipo": "esfera", "centro": [2, 0, 0], "radio": 0.5, "material": "rojo" }"#,
+            r#"{ "tipo": "transformado", "traslacion": [0, 3, 0], "escala": [2, 1, 1],
+                 "objeto": { "tipo": "esfera", "centro": [0, 0, 0], "radio": 0.5, "material": "rojo" } }"#,
+        );
+
+        let escena = Escena::desde_json(&texto, Path::new(".")).unwrap();
+
+        let hit = escena.ray_intersect(&Vec3::new(5.0, 3.0, 0.0), &Vec3::new(-1.0, 0.0, 0.0));
+        assert!((hit.distance - 4.0).abs() < 1e-4, "{}", hit.distance);
+    }
+
+    #[test]
+    fn cubo_dinamico_transformado_es_un_error() {
+        let texto = ESCENA_MINIMA.replace(
+            r#"{ "tipo": "esfera", "centro": [2, 0, 0], "radio": 0.5, "material": "rojo" }"#,
+            r#"{ "tipo": "transformado", "rotacion": [0, 45, 0],
+                 "objeto": { "tipo": "cubo", "centro": [0, 3, 0], "tamano": 1, "material": "rojo", "dinamico": true } }"#,
+        );
+
+        let error = error_de(&texto);
+
+        assert!(
+            error.contains("objeto 1") && error.contains("dinámico"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn malla_inexistente_muestra_la_ruta() {
+        let texto = ESCENA_MINIMA.replace(
+            r#"{ "tipo": "esfera", "centro": [2, 0, 0], "radio": 0.5, "material": "rojo" }"#,
+            r#"{ "tipo": "malla", "archivo": "no_existe.obj", "material": "rojo" }"#,
+        );
+
+        let error = error_de(&texto);
+
+        assert!(error.contains("no_existe.obj"), "{}", error);
+    }
+
+    #[test]
+    fn orbita_conserva_la_altura() {
+        let orbita = Orbita {
+            radio: 100.0,
+            periodo: 20.0,
+            fase: 0.0,
+        };
+
+        let inicio = orbita.posicion(50.0, 0.0);
+        let media_vuelta = orbita.posicion(50.0, 10.0);
+
+        assert!((inicio - Vec3::new(100.0, 50.0, 0.0)).magnitude() < 1e-3);
+        assert!((media_vuelta - Vec3::new(-100.0, 50.0, 0.0)).magnitude() < 1e-3);
+    }
+}
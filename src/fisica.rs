@@ -0,0 +1,193 @@
+use crate::aabb::Aabb;
+use crate::cube::Cube;
+use crate::ray_intersect::RayIntersect;
+use nalgebra_glm::Vec3;
+
+// La simulación avanza siempre con este paso, sin importar cuánto dure cada cuadro
+pub const PASO_FISICA: f32 = 1.0 / 120.0;
+
+// Si un cuadro tarda demasiado se descarta el tiempo sobrante en vez de acumular atraso
+pub const MAX_PASOS_POR_CUADRO: u32 = 10;
+
+// Pasadas de resolución de contactos por paso; más pasadas asientan mejor las pilas
+const ITERACIONES_CONTACTO: u32 = 4;
+
+// Por debajo de esta velocidad de choque el contacto no rebota y el cubo queda en reposo
+const VELOCIDAD_REPOSO: f32 = 0.5;
+
+const ROZAMIENTO: f32 = 0.5;
+
+// Avanza un paso de tiempo: los cubos dinámicos caen con Cube::actualizar_posicion y luego
+// se separan de los cuerpos estáticos y entre ellos. Todos los cubos tienen la misma masa.
+pub fn paso(cubos: &mut [&mut Cube], estaticos: &[Aabb], delta_tiempo: f32) {
+    for cubo in cubos.iter_mut() {
+        cubo.actualizar_posicion(delta_tiempo);
+    }
+
+    for _ in 0..ITERACIONES_CONTACTO {
+        for i in 0..cubos.len() {
+            for estatico in estaticos {
+                if let Some((normal, profundidad)) = penetracion(estatico, &cubos[i].aabb()) {
+                    let cubo = &mut *cubos[i];
+                    cubo.center += normal * profundidad;
+                    let restitucion = cubo.restitucion;
+                    cubo.velocidad += impulso(&cubo.velocidad, &normal, restitucion);
+                }
+            }
+
+            let (anteriores, siguientes) = cubos.split_at_mut(i + 1);
+            let a = &mut *anteriores[i];
+            for b in siguientes.iter_mut() {
+                if let Some((normal, profundidad)) = penetracion(&a.aabb(), &b.aabb()) {
+                    // Cada cubo se mueve la mitad y se reparten el impulso
+                    a.center -= normal * (profundidad / 2.0);
+                    b.center += normal * (profundidad / 2.0);
+                    let relativa = b.velocidad - a.velocidad;
+                    let restitucion = a.restitucion.min(b.restitucion);
+                    let cambio = impulso(&relativa, &normal, restitucion) / 2.0;
+                    a.velocidad -= cambio;
+                    b.velocidad += cambio;
+                }
+            }
+        }
+    }
+}
+
+// Si las cajas se traslapan devuelve la normal que separa `b` de `a` y cuánto hay que moverlo
+fn penetracion(a: &Aabb, b: &Aabb) -> Option<(Vec3, f32)> {
+    let mut eje = 0;
+    let mut profundidad = f32::INFINITY;
+    for i in 0..3 {
+        let traslape = a.max[i].min(b.max[i]) - a.min[i].max(b.min[i]);
+        if traslape <= 0.0 {
+            return None;
+        }
+        if traslape < profundidad {
+            profundidad = traslape;
+            eje = i;
+        }
+    }
+
+    // `b` sale por el lado en el que se metió menos; así también funciona si `a` es un
+    // semiespacio con caras en el infinito
+    let mut normal = Vec3::zeros();
+    normal[eje] = if a.max[eje] - b.min[eje] <= b.max[eje] - a.min[eje] {
+        1.0
+    } else {
+        -1.0
+    };
+    Some((normal, profundidad))
+}
+
+// Cambio de la velocidad relativa `velocidad` al chocar contra una superficie con esa normal.
+// Los choques lentos no rebotan (contacto en reposo) y el rozamiento frena el deslizamiento.
+fn impulso(velocidad: &Vec3, normal: &Vec3, restitucion: f32) -> Vec3 {
+    let normal_vel = velocidad.dot(normal);
+    if normal_vel >= 0.0 {
+        return Vec3::zeros();
+    }
+
+    let restitucion = if -normal_vel < VELOCIDAD_REPOSO {
+        0.0
+    } else {
+        restitucion
+    };
+    let empuje = -(1.0 + restitucion) * normal_vel;
+
+    let tangencial = velocidad - normal * normal_vel;
+    let rapidez_tangencial = tangencial.magnitude();
+    let frenado = if rapidez_tangencial > 0.0 {
+        -tangencial * (ROZAMIENTO * empuje).min(rapidez_tangencial) / rapidez_tangencial
+    } else {
+        Vec3::zeros()
+    };
+
+    normal * empuje + frenado
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+    use crate::material::Material;
+
+    fn cubo(centro: Vec3, velocidad: Vec3) -> Cube {
+        let material = Material::new(Color::new(255, 255, 255), 10.0, [0.9, 0.1], None);
+        Cube::new(centro, 1.0, material, velocidad)
+    }
+
+    // Suelo cuya cara superior está en y = 0
+    fn suelo() -> Aabb {
+        Aabb::new(Vec3::new(-50.0, -10.0, -50.0), Vec3::new(50.0, 0.0, 50.0))
+    }
+
+    fn simular(cubos: &mut [&mut Cube], segundos: f32) {
+        for _ in 0..(segundos / PASO_FISICA) as u32 {
+            paso(cubos, &[suelo()], PASO_FISICA);
+        }
+    }
+
+    #[test]
+    fn un_cubo_cae_y_queda_en_reposo_sobre_el_suelo() {
+        let mut a = cubo(Vec3::new(0.0, 3.0, 0.0), Vec3::zeros());
+
+        simular(&mut [&mut a], 3.0);
+
+        assert!((a.center.y - 0.5).abs() < 0.01, "{}", a.center.y);
+        assert!(a.velocidad.magnitude() < 0.1, "{:?}", a.velocidad);
+    }
+
+    #[test]
+    fn la_restitucion_hace_rebotar() {
+        let mut a = cubo(Vec3::new(0.0, 3.0, 0.0), Vec3::zeros());
+        a.restitucion = 0.8;
+        let mut altura_tras_rebote: f32 = 0.0;
+        let mut toco_el_suelo = false;
+
+        for _ in 0..(2.0 / PASO_FISICA) as u32 {
+            paso(&mut [&mut a], &[suelo()], PASO_FISICA);
+            toco_el_suelo |= a.velocidad.y > 0.0;
+            if toco_el_suelo {
+                altura_tras_rebote = altura_tras_rebote.max(a.center.y);
+            }
+        }
+
+        // Sube de nuevo, pero sin llegar a la altura inicial
+        assert!(altura_tras_rebote > 1.5, "{}", altura_tras_rebote);
+        assert!(altura_tras_rebote < 3.0, "{}", altura_tras_rebote);
+    }
+
+    #[test]
+    fn el_rozamiento_detiene_un_cubo_que_se_desliza() {
+        let mut a = cubo(Vec3::new(0.0, 0.5, 0.0), Vec3::new(2.0, 0.0, 0.0));
+
+        simular(&mut [&mut a], 2.0);
+
+        assert!(a.velocidad.magnitude() < 0.01, "{:?}", a.velocidad);
+        assert!(a.center.x > 0.2 && a.center.x < 1.0, "{}", a.center.x);
+    }
+
+    #[test]
+    fn un_semiespacio_sirve_de_suelo() {
+        let mut a = cubo(Vec3::new(0.0, 3.0, 0.0), Vec3::zeros());
+        let mut semiespacio = Aabb::infinita();
+        semiespacio.max.y = 0.0;
+
+        for _ in 0..(3.0 / PASO_FISICA) as u32 {
+            paso(&mut [&mut a], &[semiespacio], PASO_FISICA);
+        }
+
+        assert!((a.center.y - 0.5).abs() < 0.01, "{}", a.center.y);
+    }
+
+    #[test]
+    fn los_cubos_se_apilan() {
+        let mut abajo = cubo(Vec3::new(0.0, 0.5, 0.0), Vec3::zeros());
+        let mut arriba = cubo(Vec3::new(0.2, 4.0, 0.0), Vec3::zeros());
+
+        simular(&mut [&mut abajo, &mut arriba], 3.0);
+
+        assert!((abajo.center.y - 0.5).abs() < 0.02, "{}", abajo.center.y);
+        assert!((arriba.center.y - 1.5).abs() < 0.02, "{}", arriba.center.y);
+    }
+}
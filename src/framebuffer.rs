@@ -1,3 +1,6 @@
+use crate::color::Color;
+use image::{ImageResult, Rgb, RgbImage};
+use std::path::Path;
 
 pub struct Framebuffer {
     pub width: usize,
@@ -37,4 +40,36 @@ impl Framebuffer {
     pub fn set_current_color(&mut self, color: u32) {
         self.current_color = color;
     }
-}
\ No newline at end of file
+
+    // Guarda el contenido del buffer como imagen; el formato sale de la extensión (p. ej. .png)
+    pub fn guardar_imagen(&self, ruta: &Path) -> ImageResult<()> {
+        let imagen = RgbImage::from_fn(self.width as u32, self.height as u32, |x, y| {
+            let color = Color::from_hex(self.buffer[y as usize * self.width + x as usize]);
+            Rgb([color.r(), color.g(), color.b()])
+        });
+        imagen.save(ruta)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn guardar_imagen_conserva_dimensiones_y_colores() {
+        let mut framebuffer = Framebuffer::new(3, 2);
+        framebuffer.buffer[0] = 0xFF0000;
+        framebuffer.buffer[5] = 0x0080FF;
+        // Nombre único por proceso para no chocar con otras corridas de las pruebas en paralelo
+        let nombre = format!("proyecto2_{}_guardar_imagen.png", std::process::id());
+        let ruta = std::env::temp_dir().join(nombre);
+
+        framebuffer.guardar_imagen(&ruta).unwrap();
+        let imagen = image::open(&ruta).unwrap().into_rgb8();
+        std::fs::remove_file(&ruta).unwrap();
+
+        assert_eq!(imagen.dimensions(), (3, 2));
+        assert_eq!(imagen.get_pixel(0, 0), &Rgb([255, 0, 0]));
+        assert_eq!(imagen.get_pixel(2, 1), &Rgb([0, 128, 255]));
+    }
+}
@@ -1,5 +1,22 @@
 use nalgebra_glm::Vec3;
 use crate::color::Color;
+use std::f32::consts::PI;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TipoLuz {
+    // Emite en todas direcciones desde `position`
+    Puntual,
+    // Luz muy lejana (sol, luna): todos los rayos son paralelos a `direccion`
+    Direccional {
+        direccion: Vec3,
+    },
+    // Cono de luz desde `position` hacia `direccion`; los ángulos son semiaperturas en radianes
+    Foco {
+        direccion: Vec3,
+        angulo_interno: f32,
+        angulo_externo: f32,
+    },
+}
 
 #[derive(Debug, Clone)]
 pub struct Light {
@@ -7,6 +24,8 @@ pub struct Light {
     pub color: Color,
     pub intensity: f32,
     pub radio_de_luz: f32, 
+    pub muestras_sombra: u32,
+    pub tipo: TipoLuz,
 }
 
 impl Light {
@@ -16,6 +35,107 @@ impl Light {
             color,
             intensity,
             radio_de_luz: radius,
+            muestras_sombra: 16,
+            tipo: TipoLuz::Puntual,
+        }
+    }
+
+    pub fn direccional(direccion: Vec3, color: Color, intensity: f32) -> Self {
+        Light {
+            tipo: TipoLuz::Direccional {
+                direccion: direccion.normalize(),
+            },
+            ..Light::new(Vec3::zeros(), color, intensity, 0.0)
+        }
+    }
+
+    pub fn foco(
+        position: Vec3,
+        direccion: Vec3,
+        color: Color,
+        intensity: f32,
+        angulo_interno: f32,
+        angulo_externo: f32,
+    ) -> Self {
+        Light {
+            tipo: TipoLuz::Foco {
+                direccion: direccion.normalize(),
+                angulo_interno,
+                angulo_externo,
+            },
+            ..Light::new(position, color, intensity, 0.0)
+        }
+    }
+
+    // Dirección normalizada desde el punto hacia la luz y distancia hasta ella
+    pub fn direccion_desde(&self, punto: &Vec3) -> (Vec3, f32) {
+        match self.tipo {
+            TipoLuz::Direccional { direccion } => (-direccion, f32::INFINITY),
+            TipoLuz::Puntual | TipoLuz::Foco { .. } => {
+                let hacia_luz = self.position - punto;
+                let distancia = hacia_luz.magnitude();
+                (hacia_luz / distancia, distancia)
+            }
         }
     }
-}
\ No newline at end of file
+
+    // Factor en [0, 1] que recorta la luz fuera del cono de un foco, con borde suave
+    pub fn atenuacion(&self, punto: &Vec3) -> f32 {
+        match self.tipo {
+            TipoLuz::Foco {
+                direccion,
+                angulo_interno,
+                angulo_externo,
+            } => {
+                let (luz_dir, _) = self.direccion_desde(punto);
+                let cos_angulo = (-luz_dir).dot(&direccion);
+                let cos_interno = angulo_interno.cos();
+                let cos_externo = angulo_externo.cos();
+                if cos_interno <= cos_externo {
+                    return if cos_angulo >= cos_externo { 1.0 } else { 0.0 };
+                }
+                let t = ((cos_angulo - cos_externo) / (cos_interno - cos_externo)).clamp(0.0, 1.0);
+                t * t * (3.0 - 2.0 * t)
+            }
+            TipoLuz::Puntual | TipoLuz::Direccional { .. } => 1.0,
+        }
+    }
+
+    // Puntos de la luz hacia los que se lanzan rayos de sombra desde `desde`.
+    // Una esfera de radio `radio_de_luz` vista desde el punto es un disco perpendicular
+    // a la dirección de la luz, así que se reparten las muestras en ese disco con una
+    // espiral de ángulo áureo, girada según el punto para no repetir el mismo patrón.
+    pub fn puntos_muestreo(&self, desde: &Vec3) -> Vec<Vec3> {
+        if self.radio_de_luz <= 0.0 || self.muestras_sombra <= 1 {
+            return vec![self.position];
+        }
+
+        let eje = (self.position - desde).normalize();
+        let auxiliar = if eje.x.abs() < 0.9 {
+            Vec3::new(1.0, 0.0, 0.0)
+        } else {
+            Vec3::new(0.0, 1.0, 0.0)
+        };
+        let tangente = eje.cross(&auxiliar).normalize();
+        let bitangente = eje.cross(&tangente);
+
+        let angulo_aureo = PI * (3.0 - 5.0_f32.sqrt());
+        let giro = ruido(desde) * 2.0 * PI;
+        let n = self.muestras_sombra as f32;
+
+        (0..self.muestras_sombra)
+            .map(|i| {
+                let i = i as f32;
+                let r = self.radio_de_luz * ((i + 0.5) / n).sqrt();
+                let theta = i * angulo_aureo + giro;
+                self.position + tangente * (r * theta.cos()) + bitangente * (r * theta.sin())
+            })
+            .collect()
+    }
+}
+
+// Valor pseudoaleatorio en [0, 1) que depende solo del punto
+fn ruido(punto: &Vec3) -> f32 {
+    let h = (punto.x * 12.9898 + punto.y * 78.233 + punto.z * 37.719).sin() * 43758.547;
+    h - h.floor()
+}
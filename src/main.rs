@@ -1,127 +1,509 @@
+mod aabb;
+mod bvh;
 mod camera;
 mod color;
+mod cone;
 mod cube;
+mod cylinder;
+mod disk;
+mod escena;
+mod fisica;
 mod framebuffer;
 mod light;
 mod material;
+mod mesh;
+mod muestreo;
+mod opciones;
+mod plane;
 mod ray_intersect;
+mod reloj;
 mod sphere;
 mod texturas;
-use crate::camera::Camera;
+mod torus;
+mod transform;
+use crate::camera::{Camera, ModoCamara, Proyeccion};
 use crate::color::Color;
-use crate::cube::Cube;
+use crate::escena::Escena;
 use crate::framebuffer::Framebuffer;
-use crate::light::Light;
-use crate::material::Material;
+use crate::light::{Light, TipoLuz};
+use crate::muestreo::{es_borde, promedio, Antialiasing, Muestreo};
+use crate::opciones::{parsear_argumentos, SalidaImagen, USO};
 use crate::ray_intersect::{Intersect, RayIntersect};
-use crate::sphere::Sphere;
-use crate::texturas::TextureManager;
-use minifb::{Key, Window, WindowOptions};
-use nalgebra_glm::{normalize, vec3, Vec3};
+use crate::reloj::Reloj;
+use crate::texturas::Filtro;
+use minifb::{Key, MouseButton, MouseMode, Window, WindowOptions};
+use nalgebra_glm::Vec3;
+use rayon::prelude::*;
+use std::env;
 use std::f32::consts::PI;
-use std::time::{Duration, Instant};
+use std::process;
+
+const LUZ_AMBIENTAL: f32 = 0.1;
+// Desplazamiento del origen de los rayos de sombra para evitar que la superficie se sombree a sí misma
+const SESGO_SOMBRA: f32 = 1e-3;
 
 fn reflector(incident: &Vec3, normal: &Vec3) -> Vec3 {
     incident - 2.0 * incident.dot(normal) * normal
 }
 
+// Dirección refractada según la ley de Snell; `eta` es n1 / n2 y la normal apunta hacia el rayo
+// incidente. Devuelve None si hay reflexión total interna.
+fn refractor(incident: &Vec3, normal: &Vec3, eta: f32) -> Option<Vec3> {
+    let cos_i = -incident.dot(normal).clamp(-1.0, 1.0);
+    let k = 1.0 - eta * eta * (1.0 - cos_i * cos_i);
+    if k < 0.0 {
+        return None;
+    }
+    Some((eta * incident + (eta * cos_i - k.sqrt()) * normal).normalize())
+}
+
+// Aproximación de Schlick a la reflectancia de Fresnel al pasar del medio n1 al medio n2
+fn fresnel_schlick(cos_i: f32, n1: f32, n2: f32) -> f32 {
+    let mut cos = cos_i;
+    if n1 > n2 {
+        let sen2_t = (n1 / n2).powi(2) * (1.0 - cos_i * cos_i);
+        if sen2_t > 1.0 {
+            return 1.0;
+        }
+        cos = (1.0 - sen2_t).sqrt();
+    }
+    let r0 = ((n1 - n2) / (n1 + n2)).powi(2);
+    r0 + (1.0 - r0) * (1.0 - cos).powi(5)
+}
+
+// Aleja el origen de un rayo secundario de la superficie, hacia el lado al que apunta `direccion`
+fn desplazar_origen(punto: &Vec3, normal: &Vec3, direccion: &Vec3) -> Vec3 {
+    if direccion.dot(normal) < 0.0 {
+        punto - normal * SESGO_SOMBRA
+    } else {
+        punto + normal * SESGO_SOMBRA
+    }
+}
+
+// Lanza un rayo de sombra desde el punto hacia la luz; los objetos emisivos no bloquean la luz
+fn en_sombra(
+    punto: &Vec3,
+    normal: &Vec3,
+    luz_dir: &Vec3,
+    distancia_luz: f32,
+    objects: &dyn RayIntersect,
+) -> bool {
+    let mut origen_sombra = desplazar_origen(punto, normal, luz_dir);
+    let mut distancia_restante = distancia_luz;
+
+    loop {
+        let tmp = objects.ray_intersect(&origen_sombra, luz_dir);
+        if !tmp.is_intersecting || tmp.distance >= distancia_restante {
+            return false;
+        }
+        if !tmp.material.emisivo {
+            return true;
+        }
+        // El rayo atraviesa el objeto emisivo y sigue buscando oclusores hacia la luz
+        origen_sombra = tmp.point + luz_dir * SESGO_SOMBRA;
+        distancia_restante -= tmp.distance + SESGO_SOMBRA;
+    }
+}
+
+// Fracción de la luz de área que se ve desde el punto (0 = sombra total, 1 = sin sombra)
+fn visibilidad_luz(punto: &Vec3, normal: &Vec3, light: &Light, objects: &dyn RayIntersect) -> f32 {
+    if let TipoLuz::Direccional { .. } = light.tipo {
+        let (luz_dir, distancia) = light.direccion_desde(punto);
+        return if en_sombra(punto, normal, &luz_dir, distancia, objects) {
+            0.0
+        } else {
+            1.0
+        };
+    }
+
+    let muestras = light.puntos_muestreo(punto);
+    let visibles = muestras
+        .iter()
+        .filter(|muestra| {
+            let hacia_luz = *muestra - punto;
+            let distancia = hacia_luz.magnitude();
+            !en_sombra(punto, normal, &(hacia_luz / distancia), distancia, objects)
+        })
+        .count();
+
+    visibles as f32 / muestras.len() as f32
+}
+
+// Ancho de la zona de la escena que cubre un píxel a lo largo de un rayo primario: `base`
+// en el origen del rayo más `apertura` por cada unidad de distancia
+#[derive(Debug, Clone, Copy)]
+struct AnchoPixel {
+    base: f32,
+    apertura: f32,
+}
+
+// Por debajo de este coseno entre el rayo y la normal la huella deja de crecer, para que las
+// superficies vistas de canto no salten al último mipmap
+const COSENO_MINIMO: f32 = 0.05;
+
+// Mipmap que corresponde al tamaño de un píxel sobre la textura. El ancho del píxel en el
+// punto de choque (más grande si la superficie está inclinada respecto al rayo) se pasa a
+// texeles con el largo de las tangentes, que dicen cuánto mide la textura en la escena. Si el
+// objeto no da tangentes se usa el nivel 0.
+fn nivel_detalle(intersect: &Intersect, ray_direction: &Vec3, ancho_pixel: AnchoPixel) -> f32 {
+    let textura = match &intersect.material.textura {
+        Some(textura) => textura,
+        None => return 0.0,
+    };
+
+    let coseno = intersect.normal.dot(ray_direction).abs() / ray_direction.magnitude();
+    let ancho =
+        (ancho_pixel.base + ancho_pixel.apertura * intersect.distance) / coseno.max(COSENO_MINIMO);
+    let escala = intersect.material.escala_uv;
+    let texeles = |eje: &Vec3, escala: f32, tamano: u32| {
+        let largo = eje.magnitude();
+        if largo > 0.0 {
+            ancho * escala.abs() * tamano as f32 / largo
+        } else {
+            0.0
+        }
+    };
+    let huella = texeles(&intersect.tangente, escala[0], textura.ancho()).max(texeles(
+        &intersect.bitangente,
+        escala[1],
+        textura.alto(),
+    ));
+
+    if huella > 1.0 {
+        huella.log2()
+    } else {
+        0.0
+    }
+}
+
 pub fn cast_ray(
     ray_origin: &Vec3,
     ray_direction: &Vec3,
-    objects: &[Box<dyn RayIntersect>],
-    light: &Light,
+    objects: &dyn RayIntersect,
+    lights: &[Light],
     color_fondo: &Color,
+    profundidad: u32,
 ) -> Color {
-    let mut intersect = Intersect::empty();
-    let mut zbuffer = f32::INFINITY;
-
-    for object in objects {
-        let tmp = object.ray_intersect(ray_origin, ray_direction);
-        if tmp.is_intersecting && tmp.distance < zbuffer {
-            zbuffer = tmp.distance;
-            intersect = tmp;
-        }
-    }
+    trazar_rayo(
+        ray_origin,
+        ray_direction,
+        None,
+        objects,
+        lights,
+        color_fondo,
+        profundidad,
+    )
+}
+
+// Como cast_ray, pero con el ancho del píxel a lo largo del rayo para elegir el mipmap de las
+// texturas con filtro trilineal. Los rayos secundarios (reflejos y refracción) usan siempre la
+// textura original.
+fn trazar_rayo(
+    ray_origin: &Vec3,
+    ray_direction: &Vec3,
+    ancho_pixel: Option<AnchoPixel>,
+    objects: &dyn RayIntersect,
+    lights: &[Light],
+    color_fondo: &Color,
+    profundidad: u32,
+) -> Color {
+    let intersect = objects.ray_intersect(ray_origin, ray_direction);
 
     if !intersect.is_intersecting {
-        return color_fondo.clone();
+        return *color_fondo;
     }
-    if let Some(ref textura) = intersect.material.textura {
-        let diffuse_color = intersect
-            .material
-            .get_diffuse_color(intersect.u, intersect.v);
-        return diffuse_color;
-    } else {
-        let diffuse_color = intersect.material.diffuse.clone();
-        return diffuse_color;
+
+    let nivel = match ancho_pixel {
+        Some(ancho_pixel) if intersect.material.filtro == Filtro::Trilineal => {
+            nivel_detalle(&intersect, ray_direction, ancho_pixel)
+        }
+        _ => 0.0,
+    };
+
+    // El color base sale de la textura si la hay, o del difuso del material
+    let color_base = intersect
+        .material
+        .get_diffuse_color(intersect.u, intersect.v, nivel);
+
+    if intersect.material.emisivo {
+        return color_base;
     }
 
-    let luz_dir = (light.position - intersect.point).normalize();
+    // Los mapas de normales y de relieve solo cambian la iluminación y la dirección de los
+    // rayos secundarios; los orígenes se siguen apartando de la superficie real
+    let normal = intersect.material.normal_sombreado(&intersect, nivel);
+
     let vista_dir = (ray_origin - intersect.point).normalize();
-    let reflector_dir = reflector(&-luz_dir, &intersect.normal);
-
-    let intensidad_difuminado = intersect.normal.dot(&luz_dir).max(0.0).min(1.0);
-    let diffuse = intersect.material.diffuse
-        * intersect.material.albedo[0]
-        * intensidad_difuminado
-        * light.intensity;
-
-    let specular_intensidad = vista_dir
-        .dot(&reflector_dir)
-        .max(0.0)
-        .powf(intersect.material.specular);
-    let specular =
-        light.color * intersect.material.albedo[1] * specular_intensidad * light.intensity;
-
-    diffuse + specular
-}
+    let mut color_local = color_base * LUZ_AMBIENTAL;
+
+    // Cada luz suma su aporte difuso y especular por separado
+    for light in lights {
+        let intensidad = light.intensity * light.atenuacion(&intersect.point);
+        if intensidad <= 0.0 {
+            continue;
+        }
+
+        // Sin luz directa tampoco hay brillo especular ni hace falta lanzar rayos de sombra
+        let (luz_dir, _) = light.direccion_desde(&intersect.point);
+        let intensidad_difuminado = normal.dot(&luz_dir).clamp(0.0, 1.0);
+        if intensidad_difuminado <= 0.0 {
+            continue;
+        }
+
+        let visibilidad = visibilidad_luz(&intersect.point, &intersect.normal, light, objects);
+        if visibilidad <= 0.0 {
+            continue;
+        }
+
+        let diffuse = color_base
+            * light.color
+            * (intersect.material.albedo[0] * intensidad_difuminado * intensidad * visibilidad);
+
+        let reflector_dir = reflector(&-luz_dir, &normal);
+        let specular_intensidad = vista_dir
+            .dot(&reflector_dir)
+            .max(0.0)
+            .powf(intersect.material.specular);
+        let specular = light.color
+            * (intersect.material.albedo[1] * specular_intensidad * intensidad * visibilidad);
+
+        color_local = color_local + diffuse + specular;
+    }
+
+    // Reflejo y refracción, limitados por la profundidad de recursión restante
+    let mut reflectividad = intersect.material.reflectividad;
+    let transparencia = intersect.material.transparencia;
+    if (reflectividad <= 0.0 && transparencia <= 0.0) || profundidad == 0 {
+        return color_local;
+    }
+
+    let mut color_refraccion = Color::new(0, 0, 0);
+    let mut transmision = 0.0;
+    if transparencia > 0.0 {
+        // Si el rayo sale del objeto se invierte la normal y el orden de los índices
+        let entrando = ray_direction.dot(&intersect.normal) < 0.0;
+        let (normal, n1, n2) = if entrando {
+            (normal, 1.0, intersect.material.indice_refraccion)
+        } else {
+            (-normal, intersect.material.indice_refraccion, 1.0)
+        };
+        let cos_i = -ray_direction.dot(&normal);
+
+        // Con reflexión total interna toda la parte transparente se refleja
+        let fresnel = match refractor(ray_direction, &normal, n1 / n2) {
+            Some(refraccion_dir) => {
+                let refraccion_origen =
+                    desplazar_origen(&intersect.point, &intersect.normal, &refraccion_dir);
+                color_refraccion = cast_ray(
+                    &refraccion_origen,
+                    &refraccion_dir,
+                    objects,
+                    lights,
+                    color_fondo,
+                    profundidad - 1,
+                );
+                fresnel_schlick(cos_i, n1, n2)
+            }
+            None => 1.0,
+        };
 
+        reflectividad += transparencia * fresnel;
+        transmision = transparencia * (1.0 - fresnel);
+    }
 
+    let mut color_reflejo = Color::new(0, 0, 0);
+    if reflectividad > 0.0 {
+        let reflejo_dir = reflector(ray_direction, &normal).normalize();
+        let reflejo_origen = desplazar_origen(&intersect.point, &intersect.normal, &reflejo_dir);
+        color_reflejo = cast_ray(
+            &reflejo_origen,
+            &reflejo_dir,
+            objects,
+            lights,
+            color_fondo,
+            profundidad - 1,
+        );
+    }
+
+    color_local * (1.0 - reflectividad - transmision).max(0.0)
+        + color_reflejo * reflectividad
+        + color_refraccion * transmision
+}
 
 pub fn render(
     framebuffer: &mut Framebuffer,
-    objects: &[Box<dyn RayIntersect>],
+    objects: &dyn RayIntersect,
     camera: &Camera,
-    light: &Light,
+    lights: &[Light],
     color_fondo: &Color,
+    profundidad_maxima: u32,
+    muestreo: &Muestreo,
 ) {
     let width = framebuffer.width as f32;
     let height = framebuffer.height as f32;
     let aspect_ratio = width / height;
-    let fov = PI / 3.0;
-    let perspective_scale = (fov * 0.5).tan();
+    let rayo = |x: f32, y: f32, lente: (f32, f32)| {
+        let screen_x = (2.0 * x) / width - 1.0;
+        let screen_y = -(2.0 * y) / height + 1.0;
+        camera.rayo(screen_x, screen_y, aspect_ratio, lente)
+    };
+    // Lado de un píxel en el plano de la imagen, a distancia 1 del ojo en perspectiva o en
+    // unidades de la escena en la ortográfica; si la cámara fija otra proporción los píxeles
+    // no son cuadrados y se toma el lado más largo
+    let aspecto = camera.aspecto.unwrap_or(aspect_ratio);
+    let lado_pixel = (1.0 / height).max(aspecto / width);
+    let ancho_pixel = match camera.proyeccion {
+        Proyeccion::Perspectiva => AnchoPixel {
+            base: 0.0,
+            apertura: 2.0 * (camera.fov * 0.5).tan() * lado_pixel,
+        },
+        Proyeccion::Ortografica => AnchoPixel {
+            base: camera.alto_ortografico * lado_pixel,
+            apertura: 0.0,
+        },
+    };
+
+    // Color del rayo que pasa por el punto (x, y) de la imagen, medido en píxeles desde la
+    // esquina superior izquierda, y por el punto `lente` de la lente de la cámara
+    let trazar = |x: f32, y: f32, lente: (f32, f32)| {
+        let (origen, direccion) = rayo(x, y, lente);
+
+        trazar_rayo(
+            &origen,
+            &direccion,
+            Some(ancho_pixel),
+            objects,
+            lights,
+            color_fondo,
+            profundidad_maxima,
+        )
+    };
+    let supermuestreo = |x: usize, y: usize| {
+        let colores: Vec<Color> = muestreo
+            .posiciones(x, y)
+            .into_iter()
+            .zip(0..)
+            .map(|((dx, dy), i)| trazar(x as f32 + dx, y as f32 + dy, muestreo.lente(x, y, i)))
+            .collect();
+        promedio(&colores)
+    };
+
+    let ancho = framebuffer.width;
+    let alto = framebuffer.height;
+    if muestreo.estrategia != Antialiasing::Adaptativo {
+        // Cada fila del framebuffer se renderiza en paralelo en el pool de rayon
+        framebuffer
+            .buffer
+            .par_chunks_mut(ancho)
+            .enumerate()
+            .for_each(|(y, fila)| {
+                for (x, pixel) in fila.iter_mut().enumerate() {
+                    *pixel = supermuestreo(x, y).to_hex();
+                }
+            });
+        return;
+    }
+
+    // Primera pasada: un rayo por el centro de cada píxel
+    let mut base = vec![Color::new(0, 0, 0); ancho * alto];
+    base.par_chunks_mut(ancho)
+        .enumerate()
+        .for_each(|(y, fila)| {
+            for (x, color) in fila.iter_mut().enumerate() {
+                *color = trazar(x as f32 + 0.5, y as f32 + 0.5, muestreo.lente(x, y, 0));
+            }
+        });
 
+    // Segunda pasada: se refinan solo los píxeles que difieren de algún vecino
     framebuffer
         .buffer
-        .iter_mut()
+        .par_chunks_mut(ancho)
         .enumerate()
-        .for_each(|(i, pixel)| {
-            let x = i % framebuffer.width;
-            let y = i / framebuffer.width;
-
-            let screen_x = (2.0 * x as f32) / width - 1.0;
-            let screen_y = -(2.0 * y as f32) / height + 1.0;
+        .for_each(|(y, fila)| {
+            for (x, pixel) in fila.iter_mut().enumerate() {
+                let centro = base[y * ancho + x];
+                let vecinos = [
+                    (x > 0).then(|| base[y * ancho + x - 1]),
+                    (x + 1 < ancho).then(|| base[y * ancho + x + 1]),
+                    (y > 0).then(|| base[(y - 1) * ancho + x]),
+                    (y + 1 < alto).then(|| base[(y + 1) * ancho + x]),
+                ];
+                let borde = vecinos
+                    .iter()
+                    .flatten()
+                    .any(|&vecino| es_borde(centro, vecino));
+                *pixel = if borde { supermuestreo(x, y) } else { centro }.to_hex();
+            }
+        });
+}
 
-            let screen_x = screen_x * aspect_ratio * perspective_scale;
-            let screen_y = screen_y * perspective_scale;
+// Renderiza un solo cuadro de la escena y lo guarda como imagen, sin abrir ventana
+fn render_headless(
+    escena: &mut Escena,
+    salida: &SalidaImagen,
+    profundidad_reflejos: u32,
+    muestreo: &Muestreo,
+) {
+    let mut framebuffer = Framebuffer::new(salida.ancho, salida.alto);
 
-            let ray_direction = normalize(&Vec3::new(screen_x, screen_y, -1.0));
-            let rotated_direction = camera.base_change(&ray_direction);
+    escena.actualizar_luces(0.0);
+    escena.preparar_cuadro();
+    let luces = escena.luces_activas();
 
-            let pixel_color =
-                cast_ray(&camera.ojo, &rotated_direction, objects, light, color_fondo);
+    render(
+        &mut framebuffer,
+        &*escena,
+        &escena.camera,
+        &luces,
+        &escena.fondo(),
+        profundidad_reflejos,
+        muestreo,
+    );
 
-            *pixel = pixel_color.to_hex();
-        });
+    if let Err(error) = framebuffer.guardar_imagen(&salida.ruta) {
+        eprintln!("No se pudo guardar {}: {}", salida.ruta.display(), error);
+        process::exit(1);
+    }
 }
 
 fn main() {
+    let opciones = match parsear_argumentos(env::args().skip(1)) {
+        Ok(opciones) => opciones,
+        Err(error) => {
+            eprintln!("{}", error);
+            eprintln!("{}", USO);
+            process::exit(2);
+        }
+    };
+    let mut escena = match Escena::cargar(&opciones.ruta_escena) {
+        Ok(escena) => escena,
+        Err(error) => {
+            eprintln!(
+                "Error al cargar la escena {}: {}",
+                opciones.ruta_escena.display(),
+                error
+            );
+            eprintln!("{}", USO);
+            process::exit(1);
+        }
+    };
+
+    let profundidad_reflejos = 3;
+
+    if let Some(salida) = &opciones.headless {
+        render_headless(
+            &mut escena,
+            salida,
+            profundidad_reflejos,
+            &opciones.muestreo,
+        );
+        return;
+    }
+
     let window_width = 450;
     let window_height = 300;
     let framebuffer_width = 1200;
     let framebuffer_height = 1000;
-    let frame_delay = Duration::from_millis(16);
-    let duracion_recorrido_luz = Duration::from_secs(10);
 
     let mut framebuffer = Framebuffer::new(framebuffer_width, framebuffer_height);
 
@@ -133,275 +515,867 @@ fn main() {
     )
     .unwrap();
 
-    let mut manejador_textura = TextureManager::new();
-    let imagen = image::open("uvg.png").unwrap().into_rgba8();
-    manejador_textura.cargar_textura("uvg", imagen);
-    let textura = manejador_textura.get_textura("uvg");
-    let uvg: Material = Material::new(Color::new(255, 255, 255), 1.0, [0.0, 0.0], textura);
-
-    let imagen = image::open("tierra.png").unwrap().into_rgba8();
-    manejador_textura.cargar_textura("tierra", imagen);
-    let textura = manejador_textura.get_textura("tierra");
-    let tierra = Material::new(Color::new(255, 255, 255), 1.0, [0.0, 0.0], textura);
-
-    let imagen = image::open("papel.png").unwrap().into_rgba8();
-    manejador_textura.cargar_textura("papel", imagen);
-    let textura = manejador_textura.get_textura("papel");
-    let papel = Material::new(Color::new(255, 255, 255), 1.0, [0.0, 0.0], textura);
-
-    let imagen: image::ImageBuffer<image::Rgba<u8>, Vec<u8>> =
-        image::open("madera.png").unwrap().into_rgba8();
-    manejador_textura.cargar_textura("madera", imagen);
-    let textura = manejador_textura.get_textura("madera");
-    let madera = Material::new(Color::new(255, 255, 255), 1.0, [0.0, 0.0], textura);
-
-    let imagen: image::ImageBuffer<image::Rgba<u8>, Vec<u8>> =
-        image::open("agua.png").unwrap().into_rgba8();
-    manejador_textura.cargar_textura("agua", imagen);
-    let textura = manejador_textura.get_textura("agua");
-    let agua = Material::new(Color::new(255, 255, 255), 1.0, [0.0, 0.0], textura);
-
-    let sol_material = Material::new(Color::new(255, 234, 100), 1.0, [0.0, 0.0], None);
-    let luna_material = Material::new(Color::new(200, 200, 255), 1.0, [0.0, 0.0], None); // Color gris azulado
-
-    let mut light = Light::new(
-        Vec3::new(100.0, 100.0, 10.0),
-        Color::new(255, 255, 255),
-        5.0,
-        3.0,
-    );
-
-    let mut esfera_amarilla = Sphere {
-        center: light.position,
-        radius: 3.0,
-        material: sol_material.clone(),
-    };
-
-    let mut camera = Camera::new(
-        Vec3::new(0.0, 0.0, 5.0),
-        Vec3::new(0.0, 0.0, 0.0),
-        Vec3::new(0.0, 1.0, 0.0),
-    );
+    // Se cuadra con el refresco de la pantalla si el render alcanza
+    window.set_target_fps(60);
 
-    let rotation_speed = PI / 10.0;
-    let velocidad_movimiento = 0.1;
+    // Las velocidades son por segundo y se multiplican por el tiempo real de cada cuadro, así
+    // la cámara se mueve igual en una máquina rápida que en una lenta
+    let rotation_speed = PI / 2.0;
+    let velocidad_movimiento = 2.0;
+    // Factor de zoom por segundo
+    let velocidad_zoom: f32 = 2.0;
+    // Radianes que gira la vista por cada píxel que se mueve el mouse
+    let sensibilidad_mouse = 0.005;
 
-    let color_inicial = Color::new(4, 12, 36);
-    let color_final = Color::new(135, 206, 235);
-    let mut color_actual = color_inicial.clone();
-    let mut siguiente_color = color_final.clone();
-    let mut tiempo_inicial = Instant::now();
-    let mut progreso_transicion = 0.0;
+    let mut modo = ModoCamara::Orbita;
+    let mut mouse_anterior: Option<(f32, f32)> = None;
 
-    let mut tiempo_luz = Instant::now();
-
-    let color_blanco = Color::new(255, 255, 255);
-    let color_amarillo = Color::new(255, 234, 100);
-    let mut color_esfera_actual = color_blanco.clone();
-
-    let radio = 100.0;
-    let duracion_recorrido_luz_secs = duracion_recorrido_luz.as_secs_f32();
-    let velocidad_angular = PI / duracion_recorrido_luz_secs;
-    let mut angulo = 0.0;
-
-    let mut es_dia = true;
+    let mut reloj = Reloj::new();
 
     while window.is_open() && !window.is_key_down(Key::Escape) {
+        let delta_tiempo = reloj.tic();
+        let distancia = velocidad_movimiento * delta_tiempo;
+        let giro = rotation_speed * delta_tiempo;
+        let zoom = velocidad_zoom.powf(delta_tiempo);
+
+        let camera = &mut escena.camera;
         if window.is_key_down(Key::W) {
-            camera.mover_enfrente(velocidad_movimiento);
+            camera.mover_enfrente(distancia);
         }
         if window.is_key_down(Key::S) {
-            camera.mover_atras(velocidad_movimiento);
+            camera.mover_atras(distancia);
         }
         if window.is_key_down(Key::A) {
-            camera.mover_izq(velocidad_movimiento);
+            camera.mover_izq(distancia);
         }
         if window.is_key_down(Key::D) {
-            camera.mover_der(velocidad_movimiento);
+            camera.mover_der(distancia);
         }
 
-        if window.is_key_down(Key::Left) {
-            camera.orbit(rotation_speed, 0.0);
+        if window.is_key_down(Key::Space) {
+            camera.mover_arriba(distancia);
         }
-        if window.is_key_down(Key::Right) {
-            camera.orbit(-rotation_speed, 0.0);
+        if window.is_key_down(Key::LeftShift) {
+            camera.mover_abajo(distancia);
         }
-        if window.is_key_down(Key::Up) {
-            camera.orbit(0.0, -rotation_speed);
+
+        // F cambia entre orbitar alrededor del centro y mirar desde el ojo (primera persona)
+        if window.is_key_pressed(Key::F, minifb::KeyRepeat::No) {
+            modo = modo.alternar();
         }
-        if window.is_key_down(Key::Down) {
-            camera.orbit(0.0, rotation_speed);
+        match modo {
+            ModoCamara::Orbita => {
+                if window.is_key_down(Key::Left) {
+                    camera.orbit(giro, 0.0);
+                }
+                if window.is_key_down(Key::Right) {
+                    camera.orbit(-giro, 0.0);
+                }
+                if window.is_key_down(Key::Up) {
+                    camera.orbit(0.0, -giro);
+                }
+                if window.is_key_down(Key::Down) {
+                    camera.orbit(0.0, giro);
+                }
+            }
+            ModoCamara::Vuelo => {
+                if window.is_key_down(Key::Left) {
+                    camera.mirar(-giro, 0.0);
+                }
+                if window.is_key_down(Key::Right) {
+                    camera.mirar(giro, 0.0);
+                }
+                if window.is_key_down(Key::Up) {
+                    camera.mirar(0.0, giro);
+                }
+                if window.is_key_down(Key::Down) {
+                    camera.mirar(0.0, -giro);
+                }
+            }
         }
 
-        if window.is_key_pressed(Key::L, minifb::KeyRepeat::No) {
-            es_dia = !es_dia;
+        // En modo vuelo, arrastrar con el botón izquierdo gira la vista según lo que se movió
+        // el mouse desde el cuadro anterior
+        let mouse = window.get_unscaled_mouse_pos(MouseMode::Pass);
+        if modo == ModoCamara::Vuelo && window.get_mouse_down(MouseButton::Left) {
+            if let (Some((x, y)), Some((x_anterior, y_anterior))) = (mouse, mouse_anterior) {
+                camera.mirar(
+                    (x - x_anterior) * sensibilidad_mouse,
+                    (y_anterior - y) * sensibilidad_mouse,
+                );
+            }
         }
+        mouse_anterior = mouse;
 
-        if es_dia {
-            esfera_amarilla.material = sol_material.clone();
-            light.color = color_amarillo.clone();
-            color_actual = Color::new(135, 206, 235);
-        } else {
-            esfera_amarilla.material = luna_material.clone();
-            light.color = color_blanco.clone();
-            color_actual = Color::new(4, 12, 36);
+        // + y - acercan y alejan; P cambia entre perspectiva y ortográfica
+        if window.is_key_down(Key::Equal) || window.is_key_down(Key::NumPadPlus) {
+            camera.zoom(zoom);
+        }
+        if window.is_key_down(Key::Minus) || window.is_key_down(Key::NumPadMinus) {
+            camera.zoom(1.0 / zoom);
+        }
+        if window.is_key_pressed(Key::P, minifb::KeyRepeat::No) {
+            camera.alternar_proyeccion();
         }
 
-        let tiempo_actual_luz = tiempo_luz.elapsed().as_secs_f32();
-        angulo = (tiempo_actual_luz * velocidad_angular) % (2.0 * PI);
+        // De noche se apagan las luces marcadas como solo_de_dia (el sol)
+        if window.is_key_pressed(Key::L, minifb::KeyRepeat::No) {
+            escena.es_dia = !escena.es_dia;
+        }
+
+        // Solo se actualiza lo que cambia entre cuadros; el resto de la escena se conserva
+        escena.actualizar_luces(reloj.transcurrido);
+        escena.avanzar_fisica(delta_tiempo);
+        escena.preparar_cuadro();
+        let luces = escena.luces_activas();
+
+        render(
+            &mut framebuffer,
+            &escena,
+            &escena.camera,
+            &luces,
+            &escena.fondo(),
+            profundidad_reflejos,
+            &opciones.muestreo,
+        );
 
-        light.position.x = radio * angulo.cos();
-        light.position.z = radio * angulo.sin();
-        esfera_amarilla.center = light.position;
+        window
+            .update_with_buffer(&framebuffer.buffer, framebuffer.width, framebuffer.height)
+            .unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cube::Cube;
+    use crate::material::Material;
+    use crate::plane::Plane;
+    use crate::sphere::Sphere;
+    use crate::texturas::{Envoltura, Textura};
+    use crate::transform::Transform;
+    use std::sync::Arc;
+
+    fn brillo(color: Color) -> u32 {
+        color.r() as u32 + color.g() as u32 + color.b() as u32
+    }
+
+    fn luz_arriba() -> Light {
+        Light::new(
+            Vec3::new(0.0, 10.0, 0.0),
+            Color::new(255, 255, 255),
+            1.0,
+            0.0,
+        )
+    }
+
+    fn cubo_de_prueba(material: Material) -> Vec<Box<dyn RayIntersect>> {
+        vec![Box::new(Cube::new(
+            Vec3::zeros(),
+            1.0,
+            material,
+            Vec3::zeros(),
+        ))]
+    }
 
-        let mut objetos: Vec<Box<dyn RayIntersect>> = Vec::new();
-        objetos.push(Box::new(esfera_amarilla.clone()));
+    #[test]
+    fn cara_iluminada_es_mas_brillante_que_cara_opuesta() {
+        let material = Material::new(Color::new(200, 200, 200), 10.0, [0.9, 0.1], None);
+        let objetos = cubo_de_prueba(material);
+        let fondo = Color::new(0, 0, 0);
 
-        //Creamos el suelo de tierra
-        objetos.push(Box::new(Cube {
-            center: Vec3::new(10.0, -310.0, -10.0),
-            size: 600.0,
-            material: tierra.clone(),
-            velocidad: vec3(0.0, 0.0, 0.0),
+        let arriba = cast_ray(
+            &Vec3::new(0.0, 5.0, 0.0),
+            &Vec3::new(0.0, -1.0, 0.0),
+            &objetos,
+            &[luz_arriba()],
+            &fondo,
+            3,
+        );
+        let abajo = cast_ray(
+            &Vec3::new(0.0, -5.0, 0.0),
+            &Vec3::new(0.0, 1.0, 0.0),
+            &objetos,
+            &[luz_arriba()],
+            &fondo,
+            3,
+        );
+
+        assert!(
+            brillo(arriba) > brillo(abajo),
+            "arriba: {}, abajo: {}",
+            arriba,
+            abajo
+        );
+    }
+
+    #[test]
+    fn cara_de_espaldas_conserva_luz_ambiental() {
+        let material = Material::new(Color::new(200, 200, 200), 10.0, [0.9, 0.1], None);
+        let objetos = cubo_de_prueba(material);
+        let fondo = Color::new(0, 0, 0);
+
+        let abajo = cast_ray(
+            &Vec3::new(0.0, -5.0, 0.0),
+            &Vec3::new(0.0, 1.0, 0.0),
+            &objetos,
+            &[luz_arriba()],
+            &fondo,
+            3,
+        );
+
+        assert_eq!(abajo, Color::new(200, 200, 200) * LUZ_AMBIENTAL);
+    }
+
+    #[test]
+    fn material_texturizado_tambien_se_ilumina() {
+        let textura = Arc::new(Textura::new(image::RgbaImage::from_pixel(
+            4,
+            4,
+            image::Rgba([180, 120, 60, 255]),
+        )));
+        let material = Material::new(Color::new(255, 255, 255), 10.0, [0.9, 0.1], Some(textura));
+        let objetos = cubo_de_prueba(material);
+        let fondo = Color::new(0, 0, 0);
+
+        let arriba = cast_ray(
+            &Vec3::new(0.0, 5.0, 0.0),
+            &Vec3::new(0.0, -1.0, 0.0),
+            &objetos,
+            &[luz_arriba()],
+            &fondo,
+            3,
+        );
+        let abajo = cast_ray(
+            &Vec3::new(0.0, -5.0, 0.0),
+            &Vec3::new(0.0, 1.0, 0.0),
+            &objetos,
+            &[luz_arriba()],
+            &fondo,
+            3,
+        );
+
+        assert!(
+            brillo(arriba) > brillo(abajo),
+            "arriba: {}, abajo: {}",
+            arriba,
+            abajo
+        );
+        assert!(
+            arriba.r() > arriba.b(),
+            "el color de la textura debe conservarse: {}",
+            arriba
+        );
+    }
+
+    #[test]
+    fn esfera_se_oscurece_lejos_de_la_luz() {
+        let material = Material::new(Color::new(200, 200, 200), 10.0, [0.9, 0.1], None);
+        let objetos: Vec<Box<dyn RayIntersect>> = vec![Box::new(Sphere {
+            center: Vec3::zeros(),
+            radius: 1.0,
+            material,
+        })];
+        let fondo = Color::new(0, 0, 0);
+
+        let polo_norte = cast_ray(
+            &Vec3::new(0.0, 5.0, 0.0),
+            &Vec3::new(0.0, -1.0, 0.0),
+            &objetos,
+            &[luz_arriba()],
+            &fondo,
+            3,
+        );
+        let ecuador = cast_ray(
+            &Vec3::new(5.0, 0.2, 0.0),
+            &Vec3::new(-1.0, 0.0, 0.0),
+            &objetos,
+            &[luz_arriba()],
+            &fondo,
+            3,
+        );
+        let polo_sur = cast_ray(
+            &Vec3::new(0.0, -5.0, 0.0),
+            &Vec3::new(0.0, 1.0, 0.0),
+            &objetos,
+            &[luz_arriba()],
+            &fondo,
+            3,
+        );
+
+        assert!(brillo(polo_norte) > brillo(ecuador));
+        assert!(brillo(ecuador) > brillo(polo_sur));
+    }
+
+    #[test]
+    fn material_emisivo_ignora_la_iluminacion() {
+        let objetos = cubo_de_prueba(Material::emisivo(Color::new(255, 234, 100)));
+        let fondo = Color::new(0, 0, 0);
+
+        let abajo = cast_ray(
+            &Vec3::new(0.0, -5.0, 0.0),
+            &Vec3::new(0.0, 1.0, 0.0),
+            &objetos,
+            &[luz_arriba()],
+            &fondo,
+            3,
+        );
+
+        assert_eq!(abajo, Color::new(255, 234, 100));
+    }
+
+    fn suelo_con_bloque() -> Vec<Box<dyn RayIntersect>> {
+        let material = Material::new(Color::new(200, 200, 200), 10.0, [0.9, 0.1], None);
+        vec![
+            Box::new(Cube::new(
+                Vec3::new(0.0, -5.0, 0.0),
+                10.0,
+                material.clone(),
+                Vec3::zeros(),
+            )),
+            Box::new(Cube::new(
+                Vec3::new(0.0, 2.0, 0.0),
+                1.0,
+                material,
+                Vec3::zeros(),
+            )),
+        ]
+    }
+
+    #[test]
+    fn bloque_proyecta_sombra_sobre_el_suelo() {
+        let objetos = suelo_con_bloque();
+        let fondo = Color::new(0, 0, 0);
+
+        let bajo_el_bloque = cast_ray(
+            &Vec3::new(0.0, 1.0, 0.5),
+            &Vec3::new(0.0, -1.0, 0.0),
+            &objetos,
+            &[luz_arriba()],
+            &fondo,
+            3,
+        );
+        let al_descubierto = cast_ray(
+            &Vec3::new(3.0, 1.0, 0.5),
+            &Vec3::new(0.0, -1.0, 0.0),
+            &objetos,
+            &[luz_arriba()],
+            &fondo,
+            3,
+        );
+
+        assert_eq!(bajo_el_bloque, Color::new(200, 200, 200) * LUZ_AMBIENTAL);
+        assert!(brillo(al_descubierto) > brillo(bajo_el_bloque));
+    }
+
+    #[test]
+    fn cara_iluminada_no_se_sombrea_a_si_misma() {
+        let objetos = suelo_con_bloque();
+        let bloque_solo = vec![objetos.into_iter().nth(1).unwrap()];
+        let fondo = Color::new(0, 0, 0);
+
+        let cara_superior = cast_ray(
+            &Vec3::new(0.0, 5.0, 0.0),
+            &Vec3::new(0.0, -1.0, 0.0),
+            &suelo_con_bloque(),
+            &[luz_arriba()],
+            &fondo,
+            3,
+        );
+        let sin_oclusores = cast_ray(
+            &Vec3::new(0.0, 5.0, 0.0),
+            &Vec3::new(0.0, -1.0, 0.0),
+            &bloque_solo,
+            &[luz_arriba()],
+            &fondo,
+            3,
+        );
+
+        assert!(brillo(cara_superior) > brillo(Color::new(200, 200, 200) * LUZ_AMBIENTAL));
+        assert_eq!(cara_superior, sin_oclusores);
+    }
+
+    #[test]
+    fn objetos_emisivos_no_proyectan_sombra() {
+        let mut objetos = suelo_con_bloque();
+        objetos.pop();
+        objetos.push(Box::new(Sphere {
+            center: Vec3::new(0.0, 10.0, 0.0),
+            radius: 3.0,
+            material: Material::emisivo(Color::new(255, 234, 100)),
         }));
+        let fondo = Color::new(0, 0, 0);
 
-        //Rellenamos
-        let mut contadorX = -2.0;
-        let mut contador2X = -1.5;
-        while contadorX < 2.5 {
-            objetos.push(Box::new(Cube {
-                center: Vec3::new(contadorX, 0.0, 1.0),
-                size: 0.5,
-                material: uvg.clone(),
-                velocidad: vec3(0.0, 0.0, 0.0),
-            }));
-            contadorX += 1.0;
-            if contadorX < 2.5 {
-                objetos.push(Box::new(Cube {
-                    center: Vec3::new(contador2X, 0.0, 1.0),
-                    size: 0.5,
-                    material: papel.clone(),
-                    velocidad: vec3(0.0, 0.0, 0.0),
-                }));
-            }
+        let suelo = cast_ray(
+            &Vec3::new(0.0, 1.0, 0.5),
+            &Vec3::new(0.0, -1.0, 0.0),
+            &objetos,
+            &[luz_arriba()],
+            &fondo,
+            3,
+        );
 
-            contador2X += 1.0;
-        }
-        contadorX = -1.5;
-        contador2X = -1.0;
-        while contadorX < 2.5 {
-            objetos.push(Box::new(Cube {
-                center: Vec3::new(contadorX, 0.5, 1.0),
-                size: 0.5,
-                material: uvg.clone(),
-                velocidad: vec3(0.0, 0.0, 0.0),
-            }));
-            contadorX += 1.0;
-            if contadorX < 2.5 {
-                objetos.push(Box::new(Cube {
-                    center: Vec3::new(contador2X, 0.5, 1.0),
-                    size: 0.5,
-                    material: papel.clone(),
-                    velocidad: vec3(0.0, 0.0, 0.0),
-                }));
-            }
+        assert!(brillo(suelo) > brillo(Color::new(200, 200, 200) * LUZ_AMBIENTAL));
+    }
 
-            contador2X += 1.0;
-        }
-        contadorX = -1.0;
-        contador2X = -0.5;
-        while contadorX < 1.5 {
-            objetos.push(Box::new(Cube {
-                center: Vec3::new(contadorX, 1.0, 1.0),
-                size: 0.5,
-                material: uvg.clone(),
-                velocidad: vec3(0.0, 0.0, 0.0),
-            }));
-            contadorX += 1.0;
-            if contadorX < 1.5 {
-                objetos.push(Box::new(Cube {
-                    center: Vec3::new(contador2X, 1.0, 1.0),
-                    size: 0.5,
-                    material: papel.clone(),
-                    velocidad: vec3(0.0, 0.0, 0.0),
-                }));
+    #[test]
+    fn luz_de_area_produce_penumbra() {
+        let objetos = suelo_con_bloque();
+        let mut luz = luz_arriba();
+        luz.radio_de_luz = 2.0;
+        luz.muestras_sombra = 32;
+        let normal = Vec3::new(0.0, 1.0, 0.0);
+
+        let umbra = visibilidad_luz(&Vec3::new(0.0, 0.0, 0.0), &normal, &luz, &objetos);
+        let penumbra = visibilidad_luz(&Vec3::new(0.6, 0.0, 0.0), &normal, &luz, &objetos);
+        let descubierto = visibilidad_luz(&Vec3::new(4.0, 0.0, 0.0), &normal, &luz, &objetos);
+
+        assert_eq!(umbra, 0.0);
+        assert!(penumbra > 0.0 && penumbra < 1.0, "penumbra: {}", penumbra);
+        assert_eq!(descubierto, 1.0);
+    }
+
+    #[test]
+    fn luz_puntual_da_sombra_dura() {
+        let objetos = suelo_con_bloque();
+        let luz = luz_arriba();
+        let normal = Vec3::new(0.0, 1.0, 0.0);
+
+        let visibilidad = visibilidad_luz(&Vec3::new(0.6, 0.0, 0.0), &normal, &luz, &objetos);
+
+        assert_eq!(visibilidad, 0.0);
+    }
+
+    #[test]
+    fn superficie_reflectante_refleja_la_escena() {
+        let espejo = Material {
+            reflectividad: 1.0,
+            ..Material::new(Color::new(0, 0, 0), 10.0, [0.0, 0.0], None)
+        };
+        let objetos: Vec<Box<dyn RayIntersect>> = vec![
+            Box::new(Cube::new(
+                Vec3::new(0.0, -5.0, 0.0),
+                10.0,
+                espejo,
+                Vec3::zeros(),
+            )),
+            Box::new(Sphere {
+                center: Vec3::new(2.0, 2.0, 0.0),
+                radius: 0.5,
+                material: Material::emisivo(Color::new(255, 0, 0)),
+            }),
+        ];
+        let fondo = Color::new(0, 0, 0);
+        let origen = Vec3::new(-2.0, 2.0, 0.0);
+        let direccion = Vec3::new(1.0, -1.0, 0.0).normalize();
+
+        let con_reflejo = cast_ray(&origen, &direccion, &objetos, &[luz_arriba()], &fondo, 1);
+        let sin_recursion = cast_ray(&origen, &direccion, &objetos, &[luz_arriba()], &fondo, 0);
+
+        assert_eq!(con_reflejo, Color::new(255, 0, 0));
+        assert_eq!(sin_recursion, Color::new(0, 0, 0));
+    }
+
+    #[test]
+    fn profundidad_limita_reflejos_entre_espejos() {
+        let espejo = Material {
+            reflectividad: 0.5,
+            ..Material::new(Color::new(100, 100, 100), 10.0, [0.9, 0.0], None)
+        };
+        let objetos: Vec<Box<dyn RayIntersect>> = vec![
+            Box::new(Cube::new(
+                Vec3::new(0.0, -5.0, 0.0),
+                10.0,
+                espejo.clone(),
+                Vec3::zeros(),
+            )),
+            Box::new(Cube::new(
+                Vec3::new(0.0, 7.0, 0.0),
+                10.0,
+                espejo,
+                Vec3::zeros(),
+            )),
+        ];
+        let fondo = Color::new(0, 0, 0);
+        let origen = Vec3::new(0.0, 1.0, 0.0);
+        let direccion = Vec3::new(0.0, -1.0, 0.0);
+
+        // Entre dos espejos paralelos el rayo rebotaría para siempre sin el límite de profundidad
+        let color = cast_ray(&origen, &direccion, &objetos, &[luz_arriba()], &fondo, 8);
+
+        assert!(brillo(color) > 0);
+    }
+
+    #[test]
+    fn refraccion_cumple_la_ley_de_snell() {
+        let incidente = Vec3::new(1.0, -1.0, 0.0).normalize();
+        let normal = Vec3::new(0.0, 1.0, 0.0);
+
+        let refractado = refractor(&incidente, &normal, 1.0 / 1.5).unwrap();
+
+        let sen_i = incidente.x.abs();
+        let sen_t = refractado.x.abs();
+        assert!((sen_i - 1.5 * sen_t).abs() < 1e-5);
+        assert!(refractado.y < 0.0);
+    }
+
+    #[test]
+    fn reflexion_total_interna_mas_alla_del_angulo_critico() {
+        // Del vidrio al aire el ángulo crítico es ~41.8°, a 60° no hay rayo refractado
+        let incidente = Vec3::new(60f32.to_radians().sin(), -60f32.to_radians().cos(), 0.0);
+        let normal = Vec3::new(0.0, 1.0, 0.0);
+
+        assert!(refractor(&incidente, &normal, 1.5).is_none());
+        assert_eq!(fresnel_schlick(60f32.to_radians().cos(), 1.5, 1.0), 1.0);
+    }
+
+    #[test]
+    fn fresnel_crece_en_angulos_rasantes() {
+        let frontal = fresnel_schlick(1.0, 1.0, 1.5);
+        let rasante = fresnel_schlick(0.05, 1.0, 1.5);
+
+        assert!((frontal - 0.04).abs() < 1e-5);
+        assert!(rasante > 0.5);
+    }
+
+    #[test]
+    fn objeto_transparente_deja_ver_lo_que_hay_detras() {
+        let vidrio = Material {
+            transparencia: 1.0,
+            indice_refraccion: 1.5,
+            ..Material::new(Color::new(255, 255, 255), 10.0, [0.0, 0.0], None)
+        };
+        let objetos: Vec<Box<dyn RayIntersect>> = vec![
+            Box::new(Sphere {
+                center: Vec3::zeros(),
+                radius: 1.0,
+                material: vidrio,
+            }),
+            Box::new(Cube::new(
+                Vec3::new(0.0, 0.0, -10.0),
+                4.0,
+                Material::emisivo(Color::new(0, 0, 255)),
+                Vec3::zeros(),
+            )),
+        ];
+        let fondo = Color::new(0, 0, 0);
+
+        let color = cast_ray(
+            &Vec3::new(0.0, 0.0, 5.0),
+            &Vec3::new(0.0, 0.0, -1.0),
+            &objetos,
+            &[luz_arriba()],
+            &fondo,
+            3,
+        );
+
+        assert!(color.b() > 200, "color: {}", color);
+        assert_eq!(color.r(), 0);
+    }
+
+    #[test]
+    fn varias_luces_suman_su_aporte() {
+        let material = Material::new(Color::new(100, 100, 100), 10.0, [0.9, 0.0], None);
+        let objetos = cubo_de_prueba(material);
+        let fondo = Color::new(0, 0, 0);
+        let luz_lateral = Light::new(
+            Vec3::new(10.0, 10.0, 0.0),
+            Color::new(255, 255, 255),
+            0.5,
+            0.0,
+        );
+        let origen = Vec3::new(0.0, 5.0, 0.0);
+        let direccion = Vec3::new(0.0, -1.0, 0.0);
+
+        let una = cast_ray(&origen, &direccion, &objetos, &[luz_arriba()], &fondo, 0);
+        let dos = cast_ray(
+            &origen,
+            &direccion,
+            &objetos,
+            &[luz_arriba(), luz_lateral],
+            &fondo,
+            0,
+        );
+
+        assert!(brillo(dos) > brillo(una), "una: {}, dos: {}", una, dos);
+    }
+
+    #[test]
+    fn luz_direccional_ilumina_y_proyecta_sombra() {
+        let objetos = suelo_con_bloque();
+        let luz = Light::direccional(Vec3::new(0.0, -1.0, 0.0), Color::new(255, 255, 255), 1.0);
+        let normal = Vec3::new(0.0, 1.0, 0.0);
+
+        assert_eq!(
+            visibilidad_luz(&Vec3::new(0.0, 0.0, 0.0), &normal, &luz, &objetos),
+            0.0
+        );
+        // A diferencia de una luz puntual, la sombra de una direccional no se ensancha
+        assert_eq!(
+            visibilidad_luz(&Vec3::new(0.6, 0.0, 0.0), &normal, &luz, &objetos),
+            1.0
+        );
+    }
+
+    #[test]
+    fn foco_solo_ilumina_dentro_de_su_cono() {
+        let material = Material::new(Color::new(200, 200, 200), 10.0, [0.9, 0.0], None);
+        let objetos: Vec<Box<dyn RayIntersect>> = vec![Box::new(Cube::new(
+            Vec3::new(0.0, -5.0, 0.0),
+            10.0,
+            material,
+            Vec3::zeros(),
+        ))];
+        let foco = Light::foco(
+            Vec3::new(0.0, 4.0, 0.0),
+            Vec3::new(0.0, -1.0, 0.0),
+            Color::new(255, 255, 255),
+            1.0,
+            0.2,
+            0.3,
+        );
+        let luces = [foco];
+        let fondo = Color::new(0, 0, 0);
+        let direccion = Vec3::new(0.0, -1.0, 0.0);
+
+        let dentro = cast_ray(
+            &Vec3::new(0.0, 2.0, 0.0),
+            &direccion,
+            &objetos,
+            &luces,
+            &fondo,
+            0,
+        );
+        let fuera = cast_ray(
+            &Vec3::new(3.0, 2.0, 0.0),
+            &direccion,
+            &objetos,
+            &luces,
+            &fondo,
+            0,
+        );
+
+        assert!(brillo(dentro) > brillo(fuera));
+        assert_eq!(fuera, Color::new(200, 200, 200) * LUZ_AMBIENTAL);
+    }
+
+    #[test]
+    fn render_paralelo_respeta_el_orden_de_filas() {
+        let material = Material::new(Color::new(200, 200, 200), 10.0, [0.9, 0.1], None);
+        let objetos: Vec<Box<dyn RayIntersect>> = vec![Box::new(Cube::new(
+            Vec3::new(0.0, 1.5, 0.0),
+            1.0,
+            material,
+            Vec3::zeros(),
+        ))];
+        let camera = Camera::new(
+            Vec3::new(0.0, 0.0, 5.0),
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        );
+        let fondo = Color::new(1, 2, 3);
+        let mut framebuffer = Framebuffer::new(40, 30);
+
+        render(
+            &mut framebuffer,
+            &objetos,
+            &camera,
+            &[luz_arriba()],
+            &fondo,
+            0,
+            &Muestreo::un_rayo(),
+        );
+
+        // El cubo está por encima del centro: se ve en las filas de arriba y no en las de abajo
+        assert_ne!(framebuffer.buffer[7 * 40 + 20], fondo.to_hex());
+        assert_eq!(framebuffer.buffer[22 * 40 + 20], fondo.to_hex());
+        assert_eq!(framebuffer.buffer[0], fondo.to_hex());
+        assert_eq!(framebuffer.buffer[40 * 30 - 1], fondo.to_hex());
+    }
+
+    // Suelo con un tablero muy fino visto desde lejos: cada píxel cubre muchos texeles
+    fn tablero_lejano(filtro: Filtro) -> Vec<u32> {
+        let tablero = image::RgbaImage::from_fn(64, 64, |x, y| {
+            if (x + y) % 2 == 0 {
+                image::Rgba([255, 255, 255, 255])
+            } else {
+                image::Rgba([0, 0, 0, 255])
             }
+        });
+        let material = Material {
+            emisivo: true,
+            filtro,
+            envoltura: Envoltura::Repetir,
+            ..Material::new(
+                Color::new(255, 255, 255),
+                0.0,
+                [0.0, 0.0],
+                Some(Arc::new(Textura::new(tablero))),
+            )
+        };
+        let objetos: Vec<Box<dyn RayIntersect>> = vec![Box::new(Plane::new(
+            Vec3::zeros(),
+            Vec3::new(0.0, 1.0, 0.0),
+            0.5,
+            material,
+        ))];
+        let camera = Camera::new(
+            Vec3::new(0.0, 50.0, 0.0),
+            Vec3::zeros(),
+            Vec3::new(0.0, 0.0, -1.0),
+        );
+        let mut framebuffer = Framebuffer::new(16, 16);
 
-            contador2X += 1.0;
-        }
-        contadorX = -0.5;
-        contador2X = 0.0;
-        while contadorX < 1.5 {
-            objetos.push(Box::new(Cube {
-                center: Vec3::new(contadorX, 1.5, 1.0),
-                size: 0.5,
-                material: uvg.clone(),
-                velocidad: vec3(0.0, 0.0, 0.0),
-            }));
-            contadorX += 1.0;
-        }
-        objetos.push(Box::new(Cube {
-            center: Vec3::new(contador2X, 1.5, 1.0),
-            size: 0.5,
-            material: papel.clone(),
-            velocidad: vec3(0.0, 0.0, 0.0),
-        }));
-        contadorX = 0.0;
-        while contadorX < 1.0 {
-            objetos.push(Box::new(Cube {
-                center: Vec3::new(contadorX, 2.0, 1.0),
-                size: 0.5,
-                material: uvg.clone(),
-                velocidad: vec3(0.0, 0.0, 0.0),
-            }));
-            contadorX += 1.0;
-        }
-        let mut contador1x = -2.0;
-        let mut contador1y = 0.5;
-
-        while contador1x < 0.5 {
-            objetos.push(Box::new(Cube {
-                center: Vec3::new(contador1x, contador1y, 1.0),
-                size: 0.5,
-                material: madera.clone(),
-                velocidad: vec3(0.0, 0.0, 0.0),
-            }));
-            contador1x += 0.5;
-            contador1y += 0.5;
-        }
+        render(
+            &mut framebuffer,
+            &objetos,
+            &camera,
+            &[],
+            &Color::new(0, 0, 0),
+            0,
+            &Muestreo::un_rayo(),
+        );
+        framebuffer.buffer
+    }
 
-        let mut contador1x = 0.5;
-        let mut contador1y = 2.0;
-        while contador1x < 2.5 {
-            objetos.push(Box::new(Cube {
-                center: Vec3::new(contador1x, contador1y, 1.0),
-                size: 0.5,
-                material: madera.clone(),
-                velocidad: vec3(2.0, 0.0, 0.0),
-            }));
-
-            contador1x += 0.5;
-            contador1y -= 0.5;
-        }
+    #[test]
+    fn nivel_detalle_crece_con_la_distancia_y_la_inclinacion() {
+        let material = Material::new(
+            Color::new(255, 255, 255),
+            0.0,
+            [0.0, 0.0],
+            Some(Arc::new(Textura::new(image::RgbaImage::new(64, 64)))),
+        );
+        // Suelo en el que la textura mide 1 x 1
+        let suelo = Plane::new(Vec3::zeros(), Vec3::new(0.0, 1.0, 0.0), 1.0, material);
+        let ancho_pixel = AnchoPixel {
+            base: 0.0,
+            apertura: 0.01,
+        };
+        let nivel = |origen: Vec3, direccion: Vec3| {
+            let intersect = suelo.ray_intersect(&origen, &direccion);
+            nivel_detalle(&intersect, &direccion, ancho_pixel)
+        };
+
+        // A 10 unidades el píxel cubre 0.1 de la textura: 6.4 texeles
+        let de_frente = nivel(Vec3::new(0.0, 10.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        assert!((de_frente - 6.4_f32.log2()).abs() < 1e-4, "{}", de_frente);
+        // Con el rayo a 60° de la normal la huella se estira al doble
+        let inclinado = Vec3::new(3.0_f32.sqrt(), -1.0, 0.0).normalize();
+        let de_lado = nivel(Vec3::new(0.0, 5.0, 0.0), inclinado);
+        assert!((de_lado - 12.8_f32.log2()).abs() < 1e-3, "{}", de_lado);
+        // Cerca de la superficie el píxel es más chico que un texel
+        assert_eq!(
+            nivel(Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, -1.0, 0.0)),
+            0.0
+        );
+    }
 
-        let mut cubo_agua = Cube::new(
-            vec3(-2.0, -1.0, 0.0), // Posición inicial
-            1.0,                 // Tamaño
-            agua.clone(),
-            vec3(0.0, 0.0, 0.0), // Velocidad inicial (quieto)
+    #[test]
+    fn trilineal_promedia_texturas_lejanas() {
+        let cercano = tablero_lejano(Filtro::Cercano);
+        let trilineal = tablero_lejano(Filtro::Trilineal);
+
+        let rojo = |pixel: &u32| Color::from_hex(*pixel).r();
+        assert!(cercano
+            .iter()
+            .any(|pixel| rojo(pixel) == 0 || rojo(pixel) == 255));
+        assert!(
+            trilineal
+                .iter()
+                .all(|pixel| (100..=156).contains(&rojo(pixel))),
+            "{:?}",
+            trilineal.iter().map(rojo).collect::<Vec<_>>()
         );
-        let delta_tiempo = 0.016; 
-        cubo_agua.actualizar_posicion(delta_tiempo);
+    }
 
-        // Añadir el cubo de agua a la lista de objetos para que se renderice
-        objetos.push(Box::new(cubo_agua.clone()));
+    // Cuadrado emisivo blanco sobre fondo negro, con el borde inclinado respecto a los píxeles
+    fn borde_inclinado(camera: &Camera, muestreo: &Muestreo) -> Vec<u8> {
+        let blanco = Material::emisivo(Color::new(255, 255, 255));
+        let cubo = Transform::desde_partes(
+            Box::new(Cube::new(Vec3::zeros(), 2.0, blanco, Vec3::zeros())),
+            Vec3::zeros(),
+            Vec3::new(0.0, 0.0, 20.0),
+            Vec3::repeat(1.0),
+        )
+        .unwrap();
+        let objetos: Vec<Box<dyn RayIntersect>> = vec![Box::new(cubo)];
+        let mut framebuffer = Framebuffer::new(24, 24);
 
-        render(&mut framebuffer, &objetos, &camera, &light, &color_actual);
+        render(
+            &mut framebuffer,
+            &objetos,
+            camera,
+            &[],
+            &Color::new(0, 0, 0),
+            0,
+            muestreo,
+        );
+        framebuffer
+            .buffer
+            .iter()
+            .map(|&pixel| Color::from_hex(pixel).r())
+            .collect()
+    }
 
-        window
-            .update_with_buffer(&framebuffer.buffer, framebuffer.width, framebuffer.height)
-            .unwrap();
+    // La cara del cubo de borde_inclinado queda a 4 unidades del ojo
+    fn camara_frontal() -> Camera {
+        Camera::new(
+            Vec3::new(0.0, 0.0, 5.0),
+            Vec3::zeros(),
+            Vec3::new(0.0, 1.0, 0.0),
+        )
+    }
 
-        std::thread::sleep(frame_delay);
+    fn intermedios(valores: &[u8]) -> usize {
+        valores.iter().filter(|&&v| v > 0 && v < 255).count()
+    }
+
+    #[test]
+    fn el_supermuestreo_suaviza_los_bordes() {
+        let sin_antialiasing = borde_inclinado(&camara_frontal(), &Muestreo::un_rayo());
+        assert_eq!(intermedios(&sin_antialiasing), 0);
+
+        for estrategia in [
+            Antialiasing::Cuadricula,
+            Antialiasing::Estratificado,
+            Antialiasing::Adaptativo,
+        ] {
+            let valores = borde_inclinado(
+                &camara_frontal(),
+                &Muestreo {
+                    estrategia,
+                    muestras: 16,
+                },
+            );
+            assert!(intermedios(&valores) > 10, "{:?}", estrategia);
+            // Lejos del borde el color no cambia: el centro sigue blanco y la esquina negra
+            assert_eq!(valores[12 * 24 + 12], 255, "{:?}", estrategia);
+            assert_eq!(valores[0], 0, "{:?}", estrategia);
+        }
+    }
+
+    #[test]
+    fn fuera_del_plano_focal_el_borde_se_difumina() {
+        let muestreo = Muestreo {
+            estrategia: Antialiasing::Estratificado,
+            muestras: 16,
+        };
+        let mut camara = camara_frontal();
+        camara.apertura = 0.3;
+
+        camara.distancia_focal = 4.0;
+        let enfocado = borde_inclinado(&camara, &muestreo);
+        camara.distancia_focal = 1.5;
+        let desenfocado = borde_inclinado(&camara, &muestreo);
+
+        // Enfocado solo queda el antialiasing del borde; desenfocado la transición se ensancha
+        assert!(
+            intermedios(&desenfocado) > 2 * intermedios(&enfocado),
+            "enfocado: {}, desenfocado: {}",
+            intermedios(&enfocado),
+            intermedios(&desenfocado)
+        );
+        assert_eq!(enfocado[12 * 24 + 12], 255);
     }
 }
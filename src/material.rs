@@ -1,5 +1,8 @@
 use crate::color::Color;
-use image::RgbaImage;
+use crate::plane::ejes_tangentes;
+use crate::ray_intersect::Intersect;
+use crate::texturas::{Envoltura, Filtro, Textura};
+use nalgebra_glm::Vec3;
 use std::sync::Arc;
 
 #[derive(Debug, Clone)]
@@ -7,27 +10,221 @@ pub struct Material {
     pub diffuse: Color,
     pub specular: f32,
     pub albedo: [f32; 2],
-    pub textura: Option<Arc<RgbaImage>>, 
+    pub textura: Option<Arc<Textura>>,
+    pub filtro: Filtro,
+    pub envoltura: Envoltura,
+    // Las coordenadas de textura se multiplican por la escala y luego se desplazan; con una
+    // escala de 4 y envoltura Repetir la textura aparece 4 veces en cada eje
+    pub escala_uv: [f32; 2],
+    pub desplazamiento_uv: [f32; 2],
+    // Mapa de normales en espacio tangente (rojo hacia +u, verde hacia arriba en la imagen,
+    // azul hacia afuera) y mapa de alturas en escala de grises. Usan el mismo muestreo que
+    // la textura de color.
+    pub mapa_normales: Option<Arc<Textura>>,
+    pub mapa_relieve: Option<Arc<Textura>>,
+    // Cuánto inclina la normal una diferencia de altura de blanco a negro entre texeles vecinos
+    pub intensidad_relieve: f32,
+    pub emisivo: bool,
+    pub reflectividad: f32,
+    pub transparencia: f32,
+    pub indice_refraccion: f32,
 }
 
 impl Material {
-    pub fn new(diffuse: Color, specular: f32, albedo: [f32; 2], textura: Option<Arc<RgbaImage>>) -> Self {
-        Self {diffuse,specular,albedo,textura,}
+    pub fn new(
+        diffuse: Color,
+        specular: f32,
+        albedo: [f32; 2],
+        textura: Option<Arc<Textura>>,
+    ) -> Self {
+        Self {
+            diffuse,
+            specular,
+            albedo,
+            textura,
+            filtro: Filtro::Cercano,
+            envoltura: Envoltura::Recortar,
+            escala_uv: [1.0, 1.0],
+            desplazamiento_uv: [0.0, 0.0],
+            mapa_normales: None,
+            mapa_relieve: None,
+            intensidad_relieve: 1.0,
+            emisivo: false,
+            reflectividad: 0.0,
+            transparencia: 0.0,
+            indice_refraccion: 1.0,
+        }
+    }
+
+    // Material que emite su propio color (sol, luna) y no recibe iluminación
+    pub fn emisivo(diffuse: Color) -> Self {
+        Self {
+            emisivo: true,
+            ..Self::new(diffuse, 0.0, [0.0, 0.0], None)
+        }
     }
 
     pub fn black() -> Self {
         Self::new(Color::new(0, 0, 0), 0.0, [0.0, 0.0], None)
     }
 
-    pub fn get_diffuse_color(&self, u: f32, v: f32) -> Color {
+    // `nivel_detalle` elige el mipmap cuando el filtro es trilineal (0 = textura original)
+    pub fn get_diffuse_color(&self, u: f32, v: f32, nivel_detalle: f32) -> Color {
         if let Some(ref textura) = self.textura {
-            let (tex_width, tex_height) = (textura.width() as f32, textura.height() as f32);
-            let (u, v) = (u.clamp(0.0, 1.0), v.clamp(0.0, 1.0));
-            let (x, y) = ((u * (tex_width - 1.0)).floor() as u32,(v * (tex_height - 1.0)).floor() as u32,);
-            let pixel = textura.get_pixel(x.min(tex_width as u32 - 1), y.min(tex_height as u32 - 1));
-            return Color::new(pixel[0], pixel[1], pixel[2]);
+            let (u, v) = self.uv_textura(u, v);
+            return textura.muestrear(u, v, self.filtro, self.envoltura, nivel_detalle);
         }
 
         self.diffuse
     }
+
+    // Normal con la que se ilumina el punto: la de la superficie, inclinada por el mapa de
+    // normales y por el de relieve si el material los tiene
+    pub fn normal_sombreado(&self, intersect: &Intersect, nivel_detalle: f32) -> Vec3 {
+        let normal = intersect.normal;
+        if self.mapa_normales.is_none() && self.mapa_relieve.is_none() {
+            return normal;
+        }
+
+        // Base ortonormal alrededor de la normal; si el objeto no dio tangentes (o se anulan,
+        // como en los polos de la esfera) cualquier par perpendicular sirve
+        let tangente = intersect.tangente - normal * normal.dot(&intersect.tangente);
+        let (tangente, bitangente) = if tangente.magnitude() > 1e-6 {
+            let tangente = tangente.normalize();
+            let bitangente = tangente.cross(&normal);
+            // La bitangente conserva el sentido en que crece v
+            if bitangente.dot(&intersect.bitangente) < 0.0 {
+                (tangente, -bitangente)
+            } else {
+                (tangente, bitangente)
+            }
+        } else {
+            ejes_tangentes(&normal)
+        };
+
+        let (u, v) = self.uv_textura(intersect.u, intersect.v);
+        let mut resultado = normal;
+        if let Some(ref mapa) = self.mapa_normales {
+            let color = mapa.muestrear(u, v, self.filtro, self.envoltura, nivel_detalle);
+            let [x, y, z] = [color.r(), color.g(), color.b()].map(|c| c as f32 / 127.5 - 1.0);
+            resultado = tangente * x - bitangente * y + normal * z;
+        }
+        if let Some(ref mapa) = self.mapa_relieve {
+            let altura = |u: f32, v: f32| {
+                let color = mapa.muestrear(u, v, self.filtro, self.envoltura, nivel_detalle);
+                (0.299 * color.r() as f32 + 0.587 * color.g() as f32 + 0.114 * color.b() as f32)
+                    / 255.0
+            };
+            // Diferencias centradas a un texel de distancia en cada eje
+            let du = 1.0 / mapa.ancho() as f32;
+            let dv = 1.0 / mapa.alto() as f32;
+            let pendiente_u = (altura(u + du, v) - altura(u - du, v)) / 2.0;
+            let pendiente_v = (altura(u, v + dv) - altura(u, v - dv)) / 2.0;
+            resultado -=
+                (tangente * pendiente_u + bitangente * pendiente_v) * self.intensidad_relieve;
+        }
+        resultado.normalize()
+    }
+
+    // Coordenadas en la textura después de aplicar la escala y el desplazamiento del material
+    fn uv_textura(&self, u: f32, v: f32) -> (f32, f32) {
+        (
+            u * self.escala_uv[0] + self.desplazamiento_uv[0],
+            v * self.escala_uv[1] + self.desplazamiento_uv[1],
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::RgbaImage;
+
+    fn textura_uniforme(color: [u8; 3]) -> Arc<Textura> {
+        let [r, g, b] = color;
+        Arc::new(Textura::new(RgbaImage::from_pixel(
+            4,
+            4,
+            image::Rgba([r, g, b, 255]),
+        )))
+    }
+
+    // Choque con una cara que mira hacia +Z, con u hacia +X y v hacia -Y
+    fn choque(material: Material) -> Intersect {
+        Intersect::new(
+            Vec3::zeros(),
+            Vec3::new(0.0, 0.0, 1.0),
+            1.0,
+            material,
+            0.5,
+            0.5,
+        )
+        .con_tangentes(Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, -1.0, 0.0))
+    }
+
+    fn base() -> Material {
+        Material::new(Color::new(255, 255, 255), 10.0, [0.9, 0.1], None)
+    }
+
+    #[test]
+    fn mapa_de_normales_plano_no_cambia_la_normal() {
+        let material = Material {
+            mapa_normales: Some(textura_uniforme([128, 128, 255])),
+            ..base()
+        };
+
+        let normal = material.normal_sombreado(&choque(material.clone()), 0.0);
+
+        assert!(
+            (normal - Vec3::new(0.0, 0.0, 1.0)).magnitude() < 0.01,
+            "{:?}",
+            normal
+        );
+    }
+
+    #[test]
+    fn mapa_de_normales_inclina_hacia_la_tangente() {
+        // Rojo alto inclina hacia +u; verde alto hacia arriba en la imagen (contra v)
+        let material = Material {
+            mapa_normales: Some(textura_uniforme([218, 218, 200])),
+            ..base()
+        };
+
+        let normal = material.normal_sombreado(&choque(material.clone()), 0.0);
+
+        assert!(
+            normal.x > 0.3 && normal.y > 0.3 && normal.z > 0.3,
+            "{:?}",
+            normal
+        );
+        assert!((normal.magnitude() - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn el_relieve_inclina_la_normal_cuesta_abajo() {
+        // La altura crece hacia +u: la normal se inclina hacia -u
+        let rampa = RgbaImage::from_fn(8, 8, |x, _| {
+            let valor = (x * 32) as u8;
+            image::Rgba([valor, valor, valor, 255])
+        });
+        let material = Material {
+            mapa_relieve: Some(Arc::new(Textura::new(rampa))),
+            intensidad_relieve: 4.0,
+            ..base()
+        };
+
+        let normal = material.normal_sombreado(&choque(material.clone()), 0.0);
+
+        assert!(normal.x < -0.1, "{:?}", normal);
+        assert!(normal.y.abs() < 1e-5, "{:?}", normal);
+    }
+
+    #[test]
+    fn sin_mapas_se_usa_la_normal_geometrica() {
+        let material = base();
+
+        let normal = material.normal_sombreado(&choque(material.clone()), 0.0);
+
+        assert_eq!(normal, Vec3::new(0.0, 0.0, 1.0));
+    }
 }
@@ -0,0 +1,291 @@
+use crate::aabb::Aabb;
+use crate::bvh::Bvh;
+use crate::material::Material;
+use crate::ray_intersect::{Intersect, RayIntersect};
+use nalgebra_glm::Vec3;
+use std::path::Path;
+
+// Por debajo de este determinante el rayo es paralelo al triángulo
+const EPSILON_TRIANGULO: f32 = 1e-8;
+
+#[derive(Clone)]
+pub struct Triangle {
+    pub vertices: [Vec3; 3],
+    // Normales por vértice; sin ellas se usa la normal de la cara
+    pub normales: Option<[Vec3; 3]>,
+    pub uvs: [(f32, f32); 3],
+    pub material: Material,
+}
+
+impl Triangle {
+    pub fn new(vertices: [Vec3; 3], material: Material) -> Self {
+        Triangle {
+            vertices,
+            normales: None,
+            uvs: [(0.0, 0.0); 3],
+            material,
+        }
+    }
+
+    fn normal_de_cara(&self) -> Vec3 {
+        let [a, b, c] = self.vertices;
+        (b - a).cross(&(c - a)).normalize()
+    }
+
+    // Coordenadas baricéntricas (w, u, v) de un punto sobre el plano del triángulo
+    fn baricentricas(&self, punto: &Vec3) -> (f32, f32, f32) {
+        let [a, b, c] = self.vertices;
+        let ab = b - a;
+        let ac = c - a;
+        let ap = punto - a;
+        let d00 = ab.dot(&ab);
+        let d01 = ab.dot(&ac);
+        let d11 = ac.dot(&ac);
+        let d20 = ap.dot(&ab);
+        let d21 = ap.dot(&ac);
+        let denominador = d00 * d11 - d01 * d01;
+        let u = (d11 * d20 - d01 * d21) / denominador;
+        let v = (d00 * d21 - d01 * d20) / denominador;
+        (1.0 - u - v, u, v)
+    }
+
+    fn interpolar_uv(&self, w: f32, u: f32, v: f32) -> (f32, f32) {
+        let [a, b, c] = self.uvs;
+        (a.0 * w + b.0 * u + c.0 * v, a.1 * w + b.1 * u + c.1 * v)
+    }
+
+    // Direcciones en las que crecen las coordenadas de textura sobre el triángulo. Si los tres
+    // vértices comparten UV no hay ninguna y quedan en cero.
+    fn ejes_uv(&self) -> (Vec3, Vec3) {
+        let [a, b, c] = self.vertices;
+        let [uv_a, uv_b, uv_c] = self.uvs;
+        let (du1, dv1) = (uv_b.0 - uv_a.0, uv_b.1 - uv_a.1);
+        let (du2, dv2) = (uv_c.0 - uv_a.0, uv_c.1 - uv_a.1);
+        let determinante = du1 * dv2 - du2 * dv1;
+        if determinante.abs() < EPSILON_TRIANGULO {
+            return (Vec3::zeros(), Vec3::zeros());
+        }
+        let (ab, ac) = (b - a, c - a);
+        (
+            (ab * dv2 - ac * dv1) / determinante,
+            (ac * du1 - ab * du2) / determinante,
+        )
+    }
+}
+
+impl RayIntersect for Triangle {
+    // Möller-Trumbore
+    fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Intersect {
+        let [a, b, c] = self.vertices;
+        let ab = b - a;
+        let ac = c - a;
+        let p = ray_direction.cross(&ac);
+        let determinante = ab.dot(&p);
+        if determinante.abs() < EPSILON_TRIANGULO {
+            return Intersect::empty();
+        }
+
+        let inv_det = 1.0 / determinante;
+        let ao = ray_origin - a;
+        let u = ao.dot(&p) * inv_det;
+        if !(0.0..=1.0).contains(&u) {
+            return Intersect::empty();
+        }
+        let q = ao.cross(&ab);
+        let v = ray_direction.dot(&q) * inv_det;
+        if v < 0.0 || u + v > 1.0 {
+            return Intersect::empty();
+        }
+        let t = ac.dot(&q) * inv_det;
+        if t <= 0.0 {
+            return Intersect::empty();
+        }
+
+        let w = 1.0 - u - v;
+        let normal = match &self.normales {
+            Some([na, nb, nc]) => (na * w + nb * u + nc * v).normalize(),
+            None => self.normal_de_cara(),
+        };
+        let (tu, tv) = self.interpolar_uv(w, u, v);
+
+        let (tangente, bitangente) = self.ejes_uv();
+
+        Intersect::new(
+            ray_origin + ray_direction * t,
+            normal,
+            t,
+            self.material.clone(),
+            tu,
+            tv,
+        )
+        .con_tangentes(tangente, bitangente)
+    }
+
+    fn get_uv(&self, point: &Vec3) -> (f32, f32) {
+        let (w, u, v) = self.baricentricas(point);
+        self.interpolar_uv(w, u, v)
+    }
+
+    fn aabb(&self) -> Aabb {
+        self.vertices
+            .iter()
+            .fold(Aabb::vacia(), |caja, vertice| caja.incluir_punto(vertice))
+    }
+}
+
+// Malla de triángulos con su propio BVH, para que un modelo grande cueste como unos pocos objetos
+pub struct Mesh {
+    triangulos: Bvh,
+}
+
+impl Mesh {
+    pub fn new(triangulos: Vec<Triangle>) -> Self {
+        let triangulos = triangulos
+            .into_iter()
+            .map(|triangulo| Box::new(triangulo) as Box<dyn RayIntersect>)
+            .collect();
+        Mesh {
+            triangulos: Bvh::new(triangulos),
+        }
+    }
+
+    // Carga todos los modelos de un archivo Wavefront OBJ con un solo material.
+    // Los materiales .mtl del archivo se ignoran.
+    pub fn cargar_obj(ruta: &Path, material: Material) -> Result<Mesh, tobj::LoadError> {
+        let opciones = tobj::LoadOptions {
+            triangulate: true,
+            single_index: true,
+            ..Default::default()
+        };
+        let (modelos, _) = tobj::load_obj(ruta, &opciones)?;
+
+        let mut triangulos = Vec::new();
+        for modelo in &modelos {
+            let malla = &modelo.mesh;
+            let posicion = |i: usize| {
+                Vec3::new(
+                    malla.positions[3 * i],
+                    malla.positions[3 * i + 1],
+                    malla.positions[3 * i + 2],
+                )
+            };
+            let normal = |i: usize| {
+                Vec3::new(
+                    malla.normals[3 * i],
+                    malla.normals[3 * i + 1],
+                    malla.normals[3 * i + 2],
+                )
+            };
+            // En OBJ la v crece hacia arriba y en las imágenes hacia abajo
+            let uv = |i: usize| (malla.texcoords[2 * i], 1.0 - malla.texcoords[2 * i + 1]);
+
+            for cara in malla.indices.chunks_exact(3) {
+                let indices = [cara[0] as usize, cara[1] as usize, cara[2] as usize];
+                let mut triangulo = Triangle::new(indices.map(posicion), material.clone());
+                if !malla.normals.is_empty() {
+                    triangulo.normales = Some(indices.map(normal));
+                }
+                if !malla.texcoords.is_empty() {
+                    triangulo.uvs = indices.map(uv);
+                }
+                triangulos.push(triangulo);
+            }
+        }
+
+        Ok(Mesh::new(triangulos))
+    }
+}
+
+impl RayIntersect for Mesh {
+    fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Intersect {
+        self.triangulos.ray_intersect(ray_origin, ray_direction)
+    }
+
+    fn get_uv(&self, _point: &Vec3) -> (f32, f32) {
+        (0.0, 0.0)
+    }
+
+    fn aabb(&self) -> Aabb {
+        self.triangulos.aabb()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+
+    fn material() -> Material {
+        Material::new(Color::new(255, 255, 255), 10.0, [0.9, 0.1], None)
+    }
+
+    fn triangulo_con_uvs() -> Triangle {
+        let mut triangulo = Triangle::new(
+            [
+                Vec3::new(0.0, 0.0, 0.0),
+                Vec3::new(1.0, 0.0, 0.0),
+                Vec3::new(0.0, 1.0, 0.0),
+            ],
+            material(),
+        );
+        triangulo.uvs = [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)];
+        triangulo
+    }
+
+    #[test]
+    fn el_rayo_choca_dentro_del_triangulo_y_no_fuera() {
+        let triangulo = triangulo_con_uvs();
+        let direccion = Vec3::new(0.0, 0.0, -1.0);
+
+        let dentro = triangulo.ray_intersect(&Vec3::new(0.25, 0.25, 1.0), &direccion);
+        let fuera = triangulo.ray_intersect(&Vec3::new(0.75, 0.75, 1.0), &direccion);
+
+        assert!(dentro.is_intersecting);
+        assert!((dentro.distance - 1.0).abs() < 1e-5);
+        assert!((dentro.normal - Vec3::new(0.0, 0.0, 1.0)).magnitude() < 1e-5);
+        assert!(!fuera.is_intersecting);
+    }
+
+    #[test]
+    fn uvs_y_normales_se_interpolan() {
+        let mut triangulo = triangulo_con_uvs();
+        triangulo.normales = Some([
+            Vec3::new(0.0, 0.0, 1.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+        ]);
+
+        let hit = triangulo.ray_intersect(&Vec3::new(0.5, 0.0, 1.0), &Vec3::new(0.0, 0.0, -1.0));
+
+        assert!((hit.u - 0.5).abs() < 1e-5 && hit.v.abs() < 1e-5);
+        assert!((hit.tangente - Vec3::new(1.0, 0.0, 0.0)).magnitude() < 1e-5);
+        assert!((hit.bitangente - Vec3::new(0.0, 1.0, 0.0)).magnitude() < 1e-5);
+        let esperada = Vec3::new(1.0, 0.0, 1.0).normalize();
+        assert!((hit.normal - esperada).magnitude() < 1e-5);
+        let (u, v) = triangulo.get_uv(&Vec3::new(0.25, 0.5, 0.0));
+        assert!((u - 0.25).abs() < 1e-5 && (v - 0.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn carga_un_obj_con_normales_y_uvs() {
+        let malla =
+            Mesh::cargar_obj(Path::new("escenas/modelos/piramide.obj"), material()).unwrap();
+
+        // Las caras inclinadas miran hacia arriba
+        let caja = malla.aabb();
+        let centro = (caja.min + caja.max) / 2.0;
+        let origen = Vec3::new(centro.x + 0.1, caja.max.y + 1.0, centro.z);
+        let hit = malla.ray_intersect(&origen, &Vec3::new(0.0, -1.0, 0.0));
+        assert!(hit.is_intersecting);
+        assert!(hit.normal.y > 0.0 && hit.normal.x > 0.0, "{:?}", hit.normal);
+
+        // La base se ve desde abajo con la normal hacia abajo
+        let hit = malla.ray_intersect(
+            &Vec3::new(centro.x, caja.min.y - 1.0, centro.z),
+            &Vec3::new(0.0, 1.0, 0.0),
+        );
+        assert!(hit.is_intersecting);
+        assert!((hit.normal - Vec3::new(0.0, -1.0, 0.0)).magnitude() < 1e-5);
+        assert!((0.0..=1.0).contains(&hit.u) && (0.0..=1.0).contains(&hit.v));
+    }
+}
@@ -0,0 +1,184 @@
+use crate::color::Color;
+
+// Separa las semillas del ruido de la lente de las de la posición dentro del píxel
+const SEMILLA_LENTE: u32 = 1 << 16;
+
+// Diferencia mínima (en cualquier canal) con un vecino para que el modo adaptativo refine un píxel
+const UMBRAL_ADAPTATIVO: u8 = 16;
+
+// Cómo se reparten los rayos dentro de cada píxel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Antialiasing {
+    // Cuadrícula regular de lado × lado puntos
+    Cuadricula,
+    // Un punto al azar dentro de cada celda de la cuadrícula
+    Estratificado,
+    // Un rayo por píxel y, solo donde cambia mucho el color entre vecinos (bordes), las
+    // muestras estratificadas
+    Adaptativo,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Muestreo {
+    pub estrategia: Antialiasing,
+    // Rayos por píxel. Las muestras se reparten en filas × columnas celdas, con la forma más
+    // cercana a un cuadrado (6 = 2 × 3, 7 = 1 × 7); la cuadrícula necesita un cuadrado perfecto.
+    pub muestras: u32,
+}
+
+impl Muestreo {
+    // Un solo rayo por el centro de cada píxel, sin antialiasing
+    pub fn un_rayo() -> Self {
+        Muestreo {
+            estrategia: Antialiasing::Cuadricula,
+            muestras: 1,
+        }
+    }
+
+    // Si la cantidad de muestras sirve para la estrategia elegida
+    pub fn es_valido(&self) -> bool {
+        let (filas, columnas) = self.forma();
+        self.muestras > 0 && (self.estrategia != Antialiasing::Cuadricula || filas == columnas)
+    }
+
+    // Filas y columnas de celdas: el divisor de `muestras` más cercano a su raíz y su pareja
+    fn forma(&self) -> (u32, u32) {
+        let muestras = self.muestras.max(1);
+        let filas = (1..=(muestras as f32).sqrt() as u32)
+            .rev()
+            .find(|filas| muestras % filas == 0)
+            .unwrap_or(1);
+        (filas, muestras / filas)
+    }
+
+    // Posiciones de las muestras dentro del píxel (x, y), como fracciones en [0, 1)
+    pub fn posiciones(&self, x: usize, y: usize) -> Vec<(f32, f32)> {
+        let (filas, columnas) = self.forma();
+        (0..filas * columnas)
+            .map(|i| {
+                let (columna, fila) = ((i % columnas) as f32, (i / columnas) as f32);
+                let (dx, dy) = match self.estrategia {
+                    Antialiasing::Cuadricula => (0.5, 0.5),
+                    Antialiasing::Estratificado | Antialiasing::Adaptativo => {
+                        (ruido(x, y, 2 * i), ruido(x, y, 2 * i + 1))
+                    }
+                };
+                ((columna + dx) / columnas as f32, (fila + dy) / filas as f32)
+            })
+            .collect()
+    }
+
+    // Punto de la lente, en [0, 1)², para la muestra `i` del píxel (x, y). Es independiente de
+    // la posición en el píxel para que el desenfoque no siga el patrón de la cuadrícula.
+    pub fn lente(&self, x: usize, y: usize, i: u32) -> (f32, f32) {
+        (
+            ruido(x, y, SEMILLA_LENTE + 2 * i),
+            ruido(x, y, SEMILLA_LENTE + 2 * i + 1),
+        )
+    }
+}
+
+// En el modo adaptativo, si el píxel difiere lo suficiente de un vecino para refinarlo
+pub fn es_borde(a: Color, b: Color) -> bool {
+    a.r().abs_diff(b.r()) > UMBRAL_ADAPTATIVO
+        || a.g().abs_diff(b.g()) > UMBRAL_ADAPTATIVO
+        || a.b().abs_diff(b.b()) > UMBRAL_ADAPTATIVO
+}
+
+pub fn promedio(colores: &[Color]) -> Color {
+    let mut suma = [0.0; 3];
+    for color in colores {
+        suma[0] += color.r() as f32;
+        suma[1] += color.g() as f32;
+        suma[2] += color.b() as f32;
+    }
+    let [r, g, b] = suma.map(|canal| (canal / colores.len() as f32).round() as u8);
+    Color::new(r, g, b)
+}
+
+// Valor pseudoaleatorio en [0, 1) que depende solo del píxel y de la muestra: cada cuadro sale
+// igual y los hilos del render no comparten estado
+fn ruido(x: usize, y: usize, i: u32) -> f32 {
+    let mut h = (x as u32).wrapping_mul(0x8da6_b343)
+        ^ (y as u32).wrapping_mul(0xd816_3841)
+        ^ i.wrapping_mul(0xcb1a_b31f);
+    h ^= h >> 13;
+    h = h.wrapping_mul(0x5bd1_e995);
+    h ^= h >> 15;
+    (h >> 8) as f32 / (1u32 << 24) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn la_cuadricula_reparte_las_muestras_por_igual() {
+        let muestreo = Muestreo {
+            estrategia: Antialiasing::Cuadricula,
+            muestras: 4,
+        };
+
+        let posiciones = muestreo.posiciones(3, 7);
+
+        assert_eq!(
+            posiciones,
+            vec![(0.25, 0.25), (0.75, 0.25), (0.25, 0.75), (0.75, 0.75)]
+        );
+        assert_eq!(Muestreo::un_rayo().posiciones(0, 0), vec![(0.5, 0.5)]);
+    }
+
+    #[test]
+    fn cada_muestra_estratificada_queda_en_su_celda() {
+        let muestreo = Muestreo {
+            estrategia: Antialiasing::Estratificado,
+            muestras: 9,
+        };
+
+        let posiciones = muestreo.posiciones(10, 20);
+
+        for (i, (x, y)) in posiciones.iter().enumerate() {
+            let (columna, fila) = ((i % 3) as f32, (i / 3) as f32);
+            assert!((columna / 3.0..(columna + 1.0) / 3.0).contains(x));
+            assert!((fila / 3.0..(fila + 1.0) / 3.0).contains(y));
+        }
+        // El patrón cambia de un píxel a otro pero es el mismo en cada cuadro
+        assert_ne!(posiciones, muestreo.posiciones(11, 20));
+        assert_eq!(posiciones, muestreo.posiciones(10, 20));
+    }
+
+    #[test]
+    fn se_usan_todas_las_muestras_pedidas() {
+        for muestras in [2, 3, 5, 6, 8] {
+            let muestreo = Muestreo {
+                estrategia: Antialiasing::Estratificado,
+                muestras,
+            };
+
+            assert_eq!(muestreo.posiciones(4, 4).len(), muestras as usize);
+            assert!(muestreo.es_valido());
+        }
+        let (filas, columnas) = Muestreo {
+            estrategia: Antialiasing::Adaptativo,
+            muestras: 6,
+        }
+        .forma();
+        assert_eq!((filas, columnas), (2, 3));
+
+        let cuadricula = |muestras| Muestreo {
+            estrategia: Antialiasing::Cuadricula,
+            muestras,
+        };
+        assert!(cuadricula(9).es_valido());
+        assert!(!cuadricula(5).es_valido());
+    }
+
+    #[test]
+    fn promedio_de_colores() {
+        let colores = [Color::new(0, 0, 0), Color::new(255, 100, 51)];
+
+        assert_eq!(promedio(&colores), Color::new(128, 50, 26));
+        assert!(es_borde(colores[0], colores[1]));
+        assert!(!es_borde(Color::new(10, 10, 10), Color::new(20, 20, 20)));
+    }
+}
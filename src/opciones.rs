@@ -0,0 +1,170 @@
+use crate::muestreo::{Antialiasing, Muestreo};
+use std::path::PathBuf;
+
+pub const USO: &str = "Uso: Proyecto2 [archivo de escena] [--headless] [--ancho N] [--alto N] \
+[--salida archivo.png] [--muestras N] [--antialiasing cuadricula|estratificado|adaptativo]";
+
+// Render de un único cuadro a un archivo PNG, sin abrir ventana
+#[derive(Debug, PartialEq)]
+pub struct SalidaImagen {
+    pub ancho: usize,
+    pub alto: usize,
+    pub ruta: PathBuf,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Opciones {
+    pub ruta_escena: PathBuf,
+    pub headless: Option<SalidaImagen>,
+    // Rayos por píxel, tanto en la ventana como en el render a archivo
+    pub muestreo: Muestreo,
+}
+
+// Interpreta los argumentos de la línea de comandos (sin el nombre del programa)
+pub fn parsear_argumentos<I: IntoIterator<Item = String>>(args: I) -> Result<Opciones, String> {
+    let mut ruta_escena = None;
+    let mut headless = false;
+    let mut ancho = 1200;
+    let mut alto = 1000;
+    let mut salida = PathBuf::from("render.png");
+    let mut muestreo = Muestreo::un_rayo();
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--headless" => headless = true,
+            "--ancho" => ancho = leer_dimension("--ancho", args.next())?,
+            "--alto" => alto = leer_dimension("--alto", args.next())?,
+            "--muestras" => {
+                let muestras = leer_dimension("--muestras", args.next())?;
+                muestreo.muestras = u32::try_from(muestras)
+                    .map_err(|_| format!("--muestras es demasiado grande: {}", muestras))?;
+            }
+            "--antialiasing" => {
+                muestreo.estrategia = match args.next().as_deref() {
+                    Some("cuadricula") => Antialiasing::Cuadricula,
+                    Some("estratificado") => Antialiasing::Estratificado,
+                    Some("adaptativo") => Antialiasing::Adaptativo,
+                    _ => {
+                        return Err(
+                            "--antialiasing debe ser cuadricula, estratificado o adaptativo"
+                                .to_string(),
+                        )
+                    }
+                }
+            }
+            "--salida" => {
+                salida = args
+                    .next()
+                    .map(PathBuf::from)
+                    .ok_or("--salida necesita una ruta")?
+            }
+            opcion if opcion.starts_with("--") => {
+                return Err(format!("opción desconocida: {}", opcion))
+            }
+            ruta => {
+                if ruta_escena.is_some() {
+                    return Err(format!("argumento de más: {}", ruta));
+                }
+                ruta_escena = Some(PathBuf::from(ruta));
+            }
+        }
+    }
+
+    if !muestreo.es_valido() {
+        return Err(format!(
+            "la cuadrícula necesita un cuadrado perfecto de muestras (4, 9, 16...), se pidieron {}",
+            muestreo.muestras
+        ));
+    }
+
+    Ok(Opciones {
+        ruta_escena: ruta_escena.unwrap_or_else(|| PathBuf::from("escenas/diorama.json")),
+        headless: headless.then_some(SalidaImagen {
+            ancho,
+            alto,
+            ruta: salida,
+        }),
+        muestreo,
+    })
+}
+
+fn leer_dimension(opcion: &str, valor: Option<String>) -> Result<usize, String> {
+    let valor = valor.ok_or_else(|| format!("{} necesita un número", opcion))?;
+    match valor.parse::<usize>() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(format!(
+            "{} debe ser un entero positivo, se recibió \"{}\"",
+            opcion, valor
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parsear(args: &[&str]) -> Result<Opciones, String> {
+        parsear_argumentos(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn sin_argumentos_abre_el_diorama_en_ventana() {
+        let opciones = parsear(&[]).unwrap();
+
+        assert_eq!(opciones.ruta_escena, PathBuf::from("escenas/diorama.json"));
+        assert_eq!(opciones.headless, None);
+        assert_eq!(opciones.muestreo, Muestreo::un_rayo());
+    }
+
+    #[test]
+    fn headless_con_dimensiones_y_salida() {
+        let opciones = parsear(&[
+            "mi_escena.json",
+            "--headless",
+            "--ancho",
+            "640",
+            "--alto",
+            "480",
+            "--salida",
+            "foto.png",
+        ])
+        .unwrap();
+
+        assert_eq!(opciones.ruta_escena, PathBuf::from("mi_escena.json"));
+        assert_eq!(
+            opciones.headless,
+            Some(SalidaImagen {
+                ancho: 640,
+                alto: 480,
+                ruta: PathBuf::from("foto.png"),
+            })
+        );
+    }
+
+    #[test]
+    fn dimensiones_invalidas_son_un_error() {
+        assert!(parsear(&["--headless", "--ancho", "0"]).is_err());
+        assert!(parsear(&["--headless", "--alto", "mil"]).is_err());
+        assert!(parsear(&["--headless", "--salida"]).is_err());
+        assert!(parsear(&["--pantalla-completa"]).is_err());
+        assert!(parsear(&["--muestras", "0"]).is_err());
+        assert!(parsear(&["--muestras", "5000000000"]).is_err());
+        assert!(parsear(&["--muestras", "5"]).is_err());
+        assert!(parsear(&["--muestras", "5", "--antialiasing", "estratificado"]).is_ok());
+        assert!(parsear(&["--antialiasing", "fxaa"]).is_err());
+    }
+
+    #[test]
+    fn muestras_y_estrategia_de_antialiasing() {
+        let opciones = parsear(&["--muestras", "16", "--antialiasing", "adaptativo"]).unwrap();
+
+        assert_eq!(
+            opciones.muestreo,
+            Muestreo {
+                estrategia: Antialiasing::Adaptativo,
+                muestras: 16,
+            }
+        );
+    }
+}
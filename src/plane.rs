@@ -0,0 +1,227 @@
+use crate::aabb::Aabb;
+use crate::material::Material;
+use crate::ray_intersect::{Intersect, RayIntersect};
+use nalgebra_glm::Vec3;
+
+// Por debajo de este valor el rayo se considera paralelo a la superficie
+pub const EPSILON_PARALELO: f32 = 1e-6;
+
+// Plano infinito que pasa por `punto`. La textura se repite cada `tamano_textura` unidades.
+#[derive(Clone)]
+pub struct Plane {
+    pub punto: Vec3,
+    pub normal: Vec3,
+    pub tamano_textura: f32,
+    pub material: Material,
+}
+
+impl Plane {
+    pub fn new(punto: Vec3, normal: Vec3, tamano_textura: f32, material: Material) -> Self {
+        Plane {
+            punto,
+            normal: normal.normalize(),
+            tamano_textura,
+            material,
+        }
+    }
+
+    // Si la normal sigue un eje, el semiespacio detrás del plano como caja infinita.
+    // La física lo usa como obstáculo igual que a un cubo estático.
+    pub fn semiespacio(&self) -> Option<Aabb> {
+        let eje = (0..3).find(|&i| (self.normal[i].abs() - 1.0).abs() < EPSILON_PARALELO)?;
+        let mut caja = Aabb::infinita();
+        if self.normal[eje] > 0.0 {
+            caja.max[eje] = self.punto[eje];
+        } else {
+            caja.min[eje] = self.punto[eje];
+        }
+        Some(caja)
+    }
+}
+
+// Dos ejes perpendiculares entre sí y a `normal`, para parametrizar superficies planas.
+// Mirando la cara de frente, el primero apunta a la derecha y el segundo hacia abajo, como
+// las coordenadas de una imagen.
+pub fn ejes_tangentes(normal: &Vec3) -> (Vec3, Vec3) {
+    let arriba = if normal.y.abs() < 0.9 {
+        Vec3::new(0.0, 1.0, 0.0)
+    } else {
+        Vec3::new(0.0, 0.0, -normal.y.signum())
+    };
+    let tangente = arriba.cross(normal).normalize();
+    let bitangente = tangente.cross(normal);
+    (tangente, bitangente)
+}
+
+// Distancia a la que el rayo cruza el plano dado, si lo cruza por delante
+pub fn interseccion_plano(
+    ray_origin: &Vec3,
+    ray_direction: &Vec3,
+    punto: &Vec3,
+    normal: &Vec3,
+) -> Option<f32> {
+    let denominador = normal.dot(ray_direction);
+    if denominador.abs() < EPSILON_PARALELO {
+        return None;
+    }
+    let t = (punto - ray_origin).dot(normal) / denominador;
+    if t > 0.0 {
+        Some(t)
+    } else {
+        None
+    }
+}
+
+impl RayIntersect for Plane {
+    fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Intersect {
+        let t = match interseccion_plano(ray_origin, ray_direction, &self.punto, &self.normal) {
+            Some(t) => t,
+            None => return Intersect::empty(),
+        };
+
+        let punto = ray_origin + ray_direction * t;
+        // El plano no tiene interior: la normal mira hacia el lado del que viene el rayo
+        let normal = if self.normal.dot(ray_direction) > 0.0 {
+            -self.normal
+        } else {
+            self.normal
+        };
+        let (u, v) = self.get_uv(&punto);
+        let (tangente, bitangente) = ejes_tangentes(&self.normal);
+
+        Intersect::new(punto, normal, t, self.material.clone(), u, v).con_tangentes(
+            tangente * self.tamano_textura,
+            bitangente * self.tamano_textura,
+        )
+    }
+
+    fn get_uv(&self, point: &Vec3) -> (f32, f32) {
+        let (tangente, bitangente) = ejes_tangentes(&self.normal);
+        let relativo = point - self.punto;
+        (
+            relativo.dot(&tangente) / self.tamano_textura,
+            relativo.dot(&bitangente) / self.tamano_textura,
+        )
+    }
+
+    fn aabb(&self) -> Aabb {
+        Aabb::infinita()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+    use crate::texturas::{Envoltura, Textura};
+    use std::sync::Arc;
+
+    fn suelo() -> Plane {
+        let material = Material::new(Color::new(255, 255, 255), 10.0, [0.9, 0.1], None);
+        Plane::new(Vec3::zeros(), Vec3::new(0.0, 1.0, 0.0), 2.0, material)
+    }
+
+    #[test]
+    fn el_plano_se_ve_desde_ambos_lados() {
+        let plano = suelo();
+
+        let arriba = plano.ray_intersect(&Vec3::new(0.0, 2.0, 0.0), &Vec3::new(0.0, -1.0, 0.0));
+        let abajo = plano.ray_intersect(&Vec3::new(0.0, -2.0, 0.0), &Vec3::new(0.0, 1.0, 0.0));
+        let paralelo = plano.ray_intersect(&Vec3::new(0.0, 2.0, 0.0), &Vec3::new(1.0, 0.0, 0.0));
+
+        assert!((arriba.distance - 2.0).abs() < 1e-5);
+        assert_eq!(arriba.normal, Vec3::new(0.0, 1.0, 0.0));
+        assert_eq!(abajo.normal, Vec3::new(0.0, -1.0, 0.0));
+        assert!(!paralelo.is_intersecting);
+    }
+
+    // Material con un tablero de 2x2 texeles que se repite como mosaico
+    fn tablero(escala: f32) -> Material {
+        let imagen = image::RgbaImage::from_fn(2, 2, |x, y| {
+            if (x + y) % 2 == 0 {
+                image::Rgba([255, 255, 255, 255])
+            } else {
+                image::Rgba([0, 0, 0, 255])
+            }
+        });
+        Material {
+            envoltura: Envoltura::Repetir,
+            escala_uv: [escala, escala],
+            ..Material::new(
+                Color::new(255, 255, 255),
+                10.0,
+                [0.9, 0.1],
+                Some(Arc::new(Textura::new(imagen))),
+            )
+        }
+    }
+
+    fn color_en(plano: &Plane, punto: &Vec3) -> Color {
+        let encuentro = plano.ray_intersect(
+            &(punto + Vec3::new(0.0, 2.0, 0.0)),
+            &Vec3::new(0.0, -1.0, 0.0),
+        );
+        encuentro
+            .material
+            .get_diffuse_color(encuentro.u, encuentro.v, 0.0)
+    }
+
+    #[test]
+    fn la_textura_se_repite() {
+        let plano = Plane {
+            material: tablero(1.0),
+            ..suelo()
+        };
+        let primero = Vec3::new(0.5, 0.0, 0.3);
+        let segundo = Vec3::new(-3.5, 0.0, 4.3);
+
+        // Las UV no se envuelven en el plano: dos copias más allá, u y v difieren en enteros
+        let (u1, v1) = plano.get_uv(&primero);
+        let (u2, v2) = plano.get_uv(&segundo);
+        assert!(((u1 - u2).abs() - 2.0).abs() < 1e-4 && ((v1 - v2).abs() - 2.0).abs() < 1e-4);
+        // ...y es la envoltura del material la que repite la textura
+        assert_eq!(color_en(&plano, &primero), color_en(&plano, &segundo));
+    }
+
+    #[test]
+    fn escalar_las_uv_achica_el_mosaico() {
+        let sin_escala = Plane {
+            material: tablero(1.0),
+            ..suelo()
+        };
+        let escalado = Plane {
+            material: tablero(2.0),
+            ..suelo()
+        };
+        let punto = Vec3::new(0.3, 0.0, 0.3);
+        let (tangente, _) = ejes_tangentes(&sin_escala.normal);
+
+        // Sin escala cada copia mide tamano_textura (2 unidades): a una unidad ya cambia el color
+        assert_ne!(
+            color_en(&sin_escala, &punto),
+            color_en(&sin_escala, &(punto + tangente))
+        );
+        // Con escala 2 cada copia mide 1 unidad: a una unidad se repite, a media cambia
+        assert_eq!(
+            color_en(&escalado, &punto),
+            color_en(&escalado, &(punto + tangente))
+        );
+        assert_ne!(
+            color_en(&escalado, &punto),
+            color_en(&escalado, &(punto + tangente * 0.5))
+        );
+    }
+
+    #[test]
+    fn el_suelo_es_un_semiespacio() {
+        let caja = suelo().semiespacio().unwrap();
+
+        assert_eq!(caja.max.y, 0.0);
+        assert_eq!(caja.min.y, f32::NEG_INFINITY);
+        let inclinado = Plane {
+            normal: Vec3::new(1.0, 1.0, 0.0).normalize(),
+            ..suelo()
+        };
+        assert!(inclinado.semiespacio().is_none());
+    }
+}
@@ -1,5 +1,7 @@
 use nalgebra_glm::Vec3;
+use crate::aabb::Aabb;
 use crate::material::Material;
+use std::any::Any;
 #[derive(Debug, Clone)]
 pub struct Intersect {
     pub point: Vec3,
@@ -9,6 +11,11 @@ pub struct Intersect {
     pub material: Material,
     pub u: f32,
     pub v: f32,
+    // Cuánto se mueve el punto sobre la superficie al crecer u y v en una unidad (dP/du y
+    // dP/dv): dan la orientación de los mapas de normales y de relieve, y su largo el tamaño de
+    // la textura en la escena para elegir el mipmap. En cero si el objeto no las calcula.
+    pub tangente: Vec3,
+    pub bitangente: Vec3,
 }
 
 impl Intersect {
@@ -21,9 +28,17 @@ impl Intersect {
             material,
             u,
             v,
+            tangente: Vec3::zeros(),
+            bitangente: Vec3::zeros(),
         }
     }
 
+    pub fn con_tangentes(mut self, tangente: Vec3, bitangente: Vec3) -> Self {
+        self.tangente = tangente;
+        self.bitangente = bitangente;
+        self
+    }
+
     pub fn empty() -> Self {
         Intersect {
             point: Vec3::zeros(),
@@ -33,11 +48,43 @@ impl Intersect {
             material: Material::black(),
             u: 0.0,
             v: 0.0,
+            tangente: Vec3::zeros(),
+            bitangente: Vec3::zeros(),
         }
     }
 }
 
-pub trait RayIntersect {
+// Send + Sync para poder compartir la escena entre los hilos del render.
+// Any permite recuperar el tipo concreto de un objeto de la escena para modificarlo.
+pub trait RayIntersect: Any + Send + Sync {
     fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Intersect;
     fn get_uv(&self, point: &Vec3) -> (f32, f32);
+    fn aabb(&self) -> Aabb;
+}
+
+// Una lista de objetos se prueba por fuerza bruta y devuelve la intersección más cercana
+impl RayIntersect for Vec<Box<dyn RayIntersect>> {
+    fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Intersect {
+        let mut intersect = Intersect::empty();
+        let mut zbuffer = f32::INFINITY;
+
+        for object in self {
+            let tmp = object.ray_intersect(ray_origin, ray_direction);
+            if tmp.is_intersecting && tmp.distance < zbuffer {
+                zbuffer = tmp.distance;
+                intersect = tmp;
+            }
+        }
+
+        intersect
+    }
+
+    fn get_uv(&self, _point: &Vec3) -> (f32, f32) {
+        (0.0, 0.0)
+    }
+
+    fn aabb(&self) -> Aabb {
+        self.iter()
+            .fold(Aabb::vacia(), |caja, object| caja.unir(&object.aabb()))
+    }
 }
\ No newline at end of file
@@ -0,0 +1,67 @@
+use std::time::{Duration, Instant};
+
+// Tope del tiempo de un cuadro, por encima de lo que tarda un render lento: si el programa se
+// trabó (ventana arrastrada, depurador), la cámara y la simulación no saltan de golpe
+const DELTA_MAXIMO: Duration = Duration::from_secs(1);
+
+// Mide el tiempo real que pasó entre un cuadro y el siguiente, para que las velocidades se
+// expresen por segundo y no por cuadro
+pub struct Reloj {
+    ultimo: Instant,
+    // Segundos acumulados desde que se creó el reloj, sumando los deltas ya recortados
+    pub transcurrido: f32,
+}
+
+impl Reloj {
+    pub fn new() -> Self {
+        Reloj::desde(Instant::now())
+    }
+
+    fn desde(inicio: Instant) -> Self {
+        Reloj {
+            ultimo: inicio,
+            transcurrido: 0.0,
+        }
+    }
+
+    // Segundos desde la llamada anterior (o desde que se creó el reloj)
+    pub fn tic(&mut self) -> f32 {
+        self.tic_en(Instant::now())
+    }
+
+    fn tic_en(&mut self, ahora: Instant) -> f32 {
+        let delta = ahora.duration_since(self.ultimo).min(DELTA_MAXIMO);
+        self.ultimo = ahora;
+        self.transcurrido += delta.as_secs_f32();
+        delta.as_secs_f32()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mide_el_tiempo_entre_cuadros() {
+        let inicio = Instant::now();
+        let mut reloj = Reloj::desde(inicio);
+
+        let primero = reloj.tic_en(inicio + Duration::from_millis(40));
+        let segundo = reloj.tic_en(inicio + Duration::from_millis(50));
+
+        assert!((primero - 0.04).abs() < 1e-6);
+        assert!((segundo - 0.01).abs() < 1e-6);
+        assert!((reloj.transcurrido - 0.05).abs() < 1e-6);
+    }
+
+    #[test]
+    fn un_cuadro_trabado_no_salta_de_golpe() {
+        let inicio = Instant::now();
+        let mut reloj = Reloj::desde(inicio);
+
+        let delta = reloj.tic_en(inicio + Duration::from_secs(5));
+
+        assert_eq!(delta, DELTA_MAXIMO.as_secs_f32());
+        assert_eq!(reloj.transcurrido, delta);
+    }
+}
@@ -1,4 +1,5 @@
 use nalgebra_glm::{Vec3, dot};
+use crate::aabb::Aabb;
 use crate::ray_intersect::{RayIntersect, Intersect};
 use crate::material::Material;
 
@@ -20,26 +21,129 @@ impl RayIntersect for Sphere {
         let discriminant = b.powi(2) - 4.0 * a * c;
 
         if discriminant > 0.0 {
-            let t = (-b - discriminant.sqrt()) / (2.0 * a);
+            let raiz = discriminant.sqrt();
+            let mut t = (-b - raiz) / (2.0 * a);
+            if t <= 0.0 {
+                // El rayo nace dentro de la esfera (p. ej. un rayo refractado): usar la salida
+                t = (-b + raiz) / (2.0 * a);
+            }
             if t > 0.0 {
                 let intersection_point = ray_origin + ray_direction * t;
                 let normal = (intersection_point - self.center).normalize();
-                return Intersect::new(intersection_point, normal, t, self.material.clone(), 0.0, 0.0); 
+                let (u, v) = self.get_uv(&intersection_point);
+                // u crece hacia el oeste a lo largo de los paralelos (una vuelta completa) y v
+                // hacia el sur (medio meridiano); en los polos las dos se anulan
+                let paralelo = Vec3::new(normal.z, 0.0, -normal.x);
+                let tangente = paralelo * (2.0 * std::f32::consts::PI * self.radius);
+                let bitangente = if paralelo.magnitude() > 1e-6 {
+                    paralelo.cross(&normal).normalize() * (std::f32::consts::PI * self.radius)
+                } else {
+                    Vec3::zeros()
+                };
+                return Intersect::new(intersection_point, normal, t, self.material.clone(), u, v)
+                    .con_tangentes(tangente, bitangente);
             }
         }
 
         Intersect::empty()
     }
 
+    // Proyección equirrectangular: u es la longitud y v la latitud (0 en el polo norte).
+    // La costura queda del lado -X; ahí u vuelve a 0 para que los dos lados usen la misma columna.
     fn get_uv(&self, point: &Vec3) -> (f32, f32) {
         let normalized_vec = (point - self.center).normalize();
         let theta = normalized_vec.z.atan2(normalized_vec.x);
-        let phi = normalized_vec.y.asin();
-        
+        // Redondeos pueden dejar y un poco fuera de [-1, 1] en los polos
+        let phi = normalized_vec.y.clamp(-1.0, 1.0).asin();
+
         // Convertir las coordenadas esféricas a UV
-        let u = 0.5 + theta / (2.0 * std::f32::consts::PI);
+        let u = (0.5 - theta / (2.0 * std::f32::consts::PI)).rem_euclid(1.0);
         let v = 0.5 - phi / std::f32::consts::PI;
-        
+
         (u, v)
     }
+
+    fn aabb(&self) -> Aabb {
+        let radio = Vec3::repeat(self.radius);
+        Aabb::new(self.center - radio, self.center + radio)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+
+    fn esfera() -> Sphere {
+        Sphere {
+            center: Vec3::new(1.0, 2.0, 3.0),
+            radius: 2.0,
+            material: Material::new(Color::new(255, 255, 255), 10.0, [0.9, 0.1], None),
+        }
+    }
+
+    fn uv_en(direccion: Vec3) -> (f32, f32) {
+        let esfera = esfera();
+        esfera.get_uv(&(esfera.center + direccion * esfera.radius))
+    }
+
+    fn cerca(a: (f32, f32), b: (f32, f32)) -> bool {
+        (a.0 - b.0).abs() < 1e-5 && (a.1 - b.1).abs() < 1e-5
+    }
+
+    #[test]
+    fn uv_en_puntos_conocidos() {
+        // Ecuador: la longitud avanza hacia la derecha vista desde afuera
+        assert!(cerca(uv_en(Vec3::new(1.0, 0.0, 0.0)), (0.5, 0.5)));
+        assert!(cerca(uv_en(Vec3::new(0.0, 0.0, 1.0)), (0.25, 0.5)));
+        assert!(cerca(uv_en(Vec3::new(0.0, 0.0, -1.0)), (0.75, 0.5)));
+        // Polos
+        assert!((uv_en(Vec3::new(0.0, 1.0, 0.0)).1).abs() < 1e-5);
+        assert!((uv_en(Vec3::new(0.0, -1.0, 0.0)).1 - 1.0).abs() < 1e-5);
+        // 45 grados de latitud norte
+        let (_, v) = uv_en(Vec3::new(1.0, 1.0, 0.0).normalize());
+        assert!((v - 0.25).abs() < 1e-5);
+    }
+
+    #[test]
+    fn la_costura_no_salta_entre_los_bordes() {
+        // A ambos lados de la costura u queda cerca de 0 o de 1, nunca fuera de [0, 1)
+        let arriba = uv_en(Vec3::new(-1.0, 0.0, 1e-4).normalize());
+        let abajo = uv_en(Vec3::new(-1.0, 0.0, -1e-4).normalize());
+        let justo = uv_en(Vec3::new(-1.0, 0.0, 0.0));
+
+        assert!((0.0..1.0).contains(&arriba.0) && (0.0..1.0).contains(&abajo.0));
+        assert!(arriba.0 < 1e-3 || arriba.0 > 1.0 - 1e-3);
+        assert!(abajo.0 < 1e-3 || abajo.0 > 1.0 - 1e-3);
+        assert_eq!(justo.0, 0.0);
+    }
+
+    #[test]
+    fn la_interseccion_lleva_las_uv() {
+        let esfera = esfera();
+        let origen = esfera.center + Vec3::new(0.0, 0.0, 10.0);
+
+        let hit = esfera.ray_intersect(&origen, &Vec3::new(0.0, 0.0, -1.0));
+
+        assert!(hit.is_intersecting);
+        assert!(cerca((hit.u, hit.v), (0.25, 0.5)));
+    }
+
+    #[test]
+    fn las_tangentes_siguen_a_las_uv() {
+        let esfera = esfera();
+        let direccion = Vec3::new(1.0, 0.5, 2.0).normalize();
+        let hit = esfera.ray_intersect(&(esfera.center + direccion * 10.0), &-direccion);
+        let paso = 1e-2;
+
+        let (u, _) = esfera.get_uv(&(hit.point + hit.tangente.normalize() * paso));
+        let (_, v) = esfera.get_uv(&(hit.point + hit.bitangente.normalize() * paso));
+
+        assert!(hit.tangente.dot(&hit.normal).abs() < 1e-5);
+        assert!(hit.bitangente.dot(&hit.normal).abs() < 1e-5);
+        assert!(u > hit.u && v > hit.v, "{} {} / {} {}", u, v, hit.u, hit.v);
+        // El largo de cada eje es lo que se recorre por unidad de u o de v
+        assert!(((u - hit.u) * hit.tangente.magnitude() - paso).abs() < 1e-4);
+        assert!(((v - hit.v) * hit.bitangente.magnitude() - paso).abs() < 1e-4);
+    }
 }
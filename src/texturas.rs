@@ -1,8 +1,169 @@
+use crate::color::Color;
+use image::RgbaImage;
 use std::collections::HashMap;
 use std::sync::Arc;
-use image::RgbaImage;
+
+// Cómo se lee la textura entre texeles
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Filtro {
+    // El texel más cercano, sin mezclar
+    #[default]
+    Cercano,
+    // Mezcla los cuatro texeles vecinos del nivel 0
+    Bilineal,
+    // Bilineal en los dos mipmaps más cercanos al tamaño del píxel y mezcla entre ambos
+    Trilineal,
+}
+
+// Qué pasa con las coordenadas de textura fuera de [0, 1]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Envoltura {
+    // Se usa el texel del borde: la textura aparece una sola vez, estirada
+    #[default]
+    Recortar,
+    // La textura se repite como mosaico
+    Repetir,
+    // Se repite alternando copias reflejadas, sin costuras entre una copia y la siguiente
+    RepetirEspejo,
+}
+
+impl Envoltura {
+    // Lleva el índice de un texel a [0, tamano)
+    fn ajustar(self, indice: i64, tamano: u32) -> u32 {
+        let tamano = tamano as i64;
+        let ajustado = match self {
+            Envoltura::Recortar => indice.clamp(0, tamano - 1),
+            Envoltura::Repetir => indice.rem_euclid(tamano),
+            Envoltura::RepetirEspejo => {
+                let periodo = indice.rem_euclid(2 * tamano);
+                if periodo < tamano {
+                    periodo
+                } else {
+                    2 * tamano - 1 - periodo
+                }
+            }
+        };
+        ajustado as u32
+    }
+}
+
+// Imagen con su cadena de mipmaps: el nivel 0 es la original y cada nivel siguiente mide la
+// mitad en cada eje, hasta llegar a 1x1
+#[derive(Debug)]
+pub struct Textura {
+    niveles: Vec<RgbaImage>,
+}
+
+impl Textura {
+    pub fn new(imagen: RgbaImage) -> Self {
+        let mut niveles = vec![imagen];
+        while let Some(ultimo) = niveles.last() {
+            if ultimo.width() <= 1 && ultimo.height() <= 1 {
+                break;
+            }
+            let siguiente = reducir(ultimo);
+            niveles.push(siguiente);
+        }
+        Textura { niveles }
+    }
+
+    pub fn ancho(&self) -> u32 {
+        self.niveles[0].width()
+    }
+
+    pub fn alto(&self) -> u32 {
+        self.niveles[0].height()
+    }
+
+    // `nivel_detalle` es el mipmap a usar (0 = imagen original, puede tener parte fraccionaria);
+    // solo lo toma en cuenta el filtro trilineal
+    pub fn muestrear(
+        &self,
+        u: f32,
+        v: f32,
+        filtro: Filtro,
+        envoltura: Envoltura,
+        nivel_detalle: f32,
+    ) -> Color {
+        let [r, g, b] = match filtro {
+            Filtro::Cercano => self.cercano(0, u, v, envoltura),
+            Filtro::Bilineal => self.bilineal(0, u, v, envoltura),
+            Filtro::Trilineal => {
+                let ultimo = self.niveles.len() - 1;
+                let nivel = nivel_detalle.clamp(0.0, ultimo as f32);
+                let base = nivel.floor() as usize;
+                let t = nivel - base as f32;
+                let fino = self.bilineal(base, u, v, envoltura);
+                if t <= 0.0 {
+                    fino
+                } else {
+                    let grueso = self.bilineal((base + 1).min(ultimo), u, v, envoltura);
+                    [0, 1, 2].map(|i| fino[i] * (1.0 - t) + grueso[i] * t)
+                }
+            }
+        };
+        Color::new(r.round() as u8, g.round() as u8, b.round() as u8)
+    }
+
+    fn texel(&self, nivel: usize, x: i64, y: i64, envoltura: Envoltura) -> [f32; 3] {
+        let imagen = &self.niveles[nivel];
+        let x = envoltura.ajustar(x, imagen.width());
+        let y = envoltura.ajustar(y, imagen.height());
+        let pixel = imagen.get_pixel(x, y);
+        [pixel[0] as f32, pixel[1] as f32, pixel[2] as f32]
+    }
+
+    fn cercano(&self, nivel: usize, u: f32, v: f32, envoltura: Envoltura) -> [f32; 3] {
+        let imagen = &self.niveles[nivel];
+        let x = (u * imagen.width() as f32).floor() as i64;
+        let y = (v * imagen.height() as f32).floor() as i64;
+        self.texel(nivel, x, y, envoltura)
+    }
+
+    fn bilineal(&self, nivel: usize, u: f32, v: f32, envoltura: Envoltura) -> [f32; 3] {
+        let imagen = &self.niveles[nivel];
+        // Los centros de los texeles están en (i + 0.5) / ancho
+        let x = u * imagen.width() as f32 - 0.5;
+        let y = v * imagen.height() as f32 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (tx, ty) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+
+        let arriba_izq = self.texel(nivel, x0, y0, envoltura);
+        let arriba_der = self.texel(nivel, x0 + 1, y0, envoltura);
+        let abajo_izq = self.texel(nivel, x0, y0 + 1, envoltura);
+        let abajo_der = self.texel(nivel, x0 + 1, y0 + 1, envoltura);
+        [0, 1, 2].map(|i| {
+            let arriba = arriba_izq[i] * (1.0 - tx) + arriba_der[i] * tx;
+            let abajo = abajo_izq[i] * (1.0 - tx) + abajo_der[i] * tx;
+            arriba * (1.0 - ty) + abajo * ty
+        })
+    }
+}
+
+// Siguiente nivel de la cadena: cada texel promedia el bloque de 2x2 que cubre. En los ejes
+// de tamaño impar el último bloque repite el texel del borde.
+fn reducir(imagen: &RgbaImage) -> RgbaImage {
+    let ancho = (imagen.width() / 2).max(1);
+    let alto = (imagen.height() / 2).max(1);
+    RgbaImage::from_fn(ancho, alto, |x, y| {
+        let mut suma = [0u32; 4];
+        for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+            let px = (2 * x + dx).min(imagen.width() - 1);
+            let py = (2 * y + dy).min(imagen.height() - 1);
+            let pixel = imagen.get_pixel(px, py);
+            for (total, canal) in suma.iter_mut().zip(pixel.0) {
+                *total += canal as u32;
+            }
+        }
+        image::Rgba(suma.map(|total| ((total + 2) / 4) as u8))
+    })
+}
+
+// Las texturas se cargan una vez con su cadena de mipmaps ya construida y los materiales
+// comparten la misma copia
 pub struct TextureManager {
-    textures: HashMap<String, Arc<RgbaImage>>,
+    textures: HashMap<String, Arc<Textura>>,
 }
 impl TextureManager {
     pub fn new() -> Self {
@@ -11,10 +172,109 @@ impl TextureManager {
         }
     }
     pub fn cargar_textura(&mut self, name: &str, image: RgbaImage) {
-        let texture = Arc::new(image);
+        let texture = Arc::new(Textura::new(image));
         self.textures.insert(name.to_string(), texture);
     }
-    pub fn get_textura(&self, name: &str) -> Option<Arc<RgbaImage>> {
+    pub fn get_textura(&self, name: &str) -> Option<Arc<Textura>> {
         self.textures.get(name).cloned()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Tablero de 4x4 texeles blancos y negros alternados
+    fn tablero() -> Textura {
+        Textura::new(RgbaImage::from_fn(4, 4, |x, y| {
+            if (x + y) % 2 == 0 {
+                image::Rgba([255, 255, 255, 255])
+            } else {
+                image::Rgba([0, 0, 0, 255])
+            }
+        }))
+    }
+
+    #[test]
+    fn la_cadena_de_mipmaps_llega_a_un_texel() {
+        let textura = Textura::new(RgbaImage::new(8, 3));
+
+        let tamanos: Vec<_> = textura
+            .niveles
+            .iter()
+            .map(|nivel| (nivel.width(), nivel.height()))
+            .collect();
+
+        assert_eq!(tamanos, vec![(8, 3), (4, 1), (2, 1), (1, 1)]);
+    }
+
+    #[test]
+    fn cada_nivel_promedia_el_anterior() {
+        let textura = tablero();
+
+        assert_eq!(textura.niveles.len(), 3);
+        assert_eq!(textura.niveles[1].get_pixel(0, 0)[0], 128);
+        assert_eq!(textura.niveles[2].get_pixel(0, 0)[0], 128);
+    }
+
+    #[test]
+    fn bilineal_mezcla_entre_texeles_vecinos() {
+        let textura = tablero();
+
+        // Justo en el centro de un texel no hay mezcla; en el borde entre dos es mitad y mitad
+        let centro = textura.muestrear(0.125, 0.125, Filtro::Bilineal, Envoltura::Recortar, 0.0);
+        let borde = textura.muestrear(0.25, 0.125, Filtro::Bilineal, Envoltura::Recortar, 0.0);
+        let cercano = textura.muestrear(0.25, 0.125, Filtro::Cercano, Envoltura::Recortar, 0.0);
+
+        assert_eq!(centro, Color::new(255, 255, 255));
+        assert_eq!(borde, Color::new(128, 128, 128));
+        assert_eq!(cercano, Color::new(0, 0, 0));
+    }
+
+    #[test]
+    fn trilineal_usa_el_mipmap_del_nivel_pedido() {
+        let textura = tablero();
+
+        let fino = textura.muestrear(0.125, 0.125, Filtro::Trilineal, Envoltura::Recortar, 0.0);
+        let medio = textura.muestrear(0.125, 0.125, Filtro::Trilineal, Envoltura::Recortar, 0.5);
+        let grueso = textura.muestrear(0.125, 0.125, Filtro::Trilineal, Envoltura::Recortar, 5.0);
+
+        assert_eq!(fino, Color::new(255, 255, 255));
+        assert_eq!(medio, Color::new(192, 192, 192));
+        assert_eq!(grueso, Color::new(128, 128, 128));
+    }
+
+    #[test]
+    fn modos_de_envoltura_fuera_del_cuadrado_unitario() {
+        // Degradado horizontal de 4 texeles: 0, 85, 170, 255
+        let degradado = Textura::new(RgbaImage::from_fn(4, 1, |x, _| {
+            let valor = (x * 85) as u8;
+            image::Rgba([valor, valor, valor, 255])
+        }));
+        let rojo = |u: f32, envoltura| {
+            degradado
+                .muestrear(u, 0.5, Filtro::Cercano, envoltura, 0.0)
+                .r()
+        };
+
+        assert_eq!(rojo(1.1, Envoltura::Recortar), 255);
+        assert_eq!(rojo(-0.9, Envoltura::Recortar), 0);
+        assert_eq!(rojo(1.1, Envoltura::Repetir), 0);
+        assert_eq!(rojo(-0.1, Envoltura::Repetir), 255);
+        assert_eq!(rojo(1.1, Envoltura::RepetirEspejo), 255);
+        assert_eq!(rojo(1.9, Envoltura::RepetirEspejo), 0);
+        assert_eq!(rojo(-0.1, Envoltura::RepetirEspejo), 0);
+    }
+
+    #[test]
+    fn bilineal_con_repeticion_mezcla_a_traves_del_borde() {
+        let textura = tablero();
+
+        // En u = 0 se mezclan el primer texel y el último de la fila, que tienen colores distintos
+        let repetir = textura.muestrear(0.0, 0.125, Filtro::Bilineal, Envoltura::Repetir, 0.0);
+        let recortar = textura.muestrear(0.0, 0.125, Filtro::Bilineal, Envoltura::Recortar, 0.0);
+
+        assert_eq!(repetir, Color::new(128, 128, 128));
+        assert_eq!(recortar, Color::new(255, 255, 255));
+    }
+}
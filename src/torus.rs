@@ -0,0 +1,259 @@
+use crate::aabb::Aabb;
+use crate::material::Material;
+use crate::ray_intersect::{Intersect, RayIntersect};
+use nalgebra_glm::Vec3;
+use std::f32::consts::PI;
+
+const EPSILON_RAIZ: f64 = 1e-9;
+
+// Toro acostado en el plano XZ: el tubo de radio `radio_menor` rodea el eje Y a una
+// distancia `radio_mayor` de `centro`
+#[derive(Clone)]
+pub struct Torus {
+    pub centro: Vec3,
+    pub radio_mayor: f32,
+    pub radio_menor: f32,
+    pub material: Material,
+}
+
+impl Torus {
+    pub fn new(centro: Vec3, radio_mayor: f32, radio_menor: f32, material: Material) -> Self {
+        Torus {
+            centro,
+            radio_mayor,
+            radio_menor,
+            material,
+        }
+    }
+
+    fn normal(&self, punto: &Vec3) -> Vec3 {
+        let p = punto - self.centro;
+        let radial = Vec3::new(p.x, 0.0, p.z);
+        let magnitud = radial.magnitude();
+        if magnitud < 1e-6 {
+            return Vec3::new(0.0, p.y.signum(), 0.0);
+        }
+        (p - radial * (self.radio_mayor / magnitud)).normalize()
+    }
+}
+
+impl RayIntersect for Torus {
+    fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Intersect {
+        // Se resuelve en f64: los coeficientes de la cuártica pierden mucha precisión
+        let o = (ray_origin - self.centro).cast::<f64>();
+        let d = ray_direction.cast::<f64>();
+        let r_mayor = self.radio_mayor as f64;
+        let r_menor = self.radio_menor as f64;
+
+        // (|p|² + R² - r²)² = 4R²(x² + z²) con p = o + t d
+        let dd = d.dot(&d);
+        let e = o.dot(&o) - r_mayor * r_mayor - r_menor * r_menor;
+        let f = o.dot(&d);
+        let cuatro_r2 = 4.0 * r_mayor * r_mayor;
+        let coeficientes = [
+            e * e - cuatro_r2 * (r_menor * r_menor - o.y * o.y),
+            4.0 * f * e + 2.0 * cuatro_r2 * o.y * d.y,
+            2.0 * dd * e + 4.0 * f * f + cuatro_r2 * d.y * d.y,
+            4.0 * dd * f,
+            dd * dd,
+        ];
+
+        let t = resolver_cuartica(&coeficientes)
+            .into_iter()
+            .map(|t| pulir_raiz(&coeficientes, t))
+            .filter(|&t| t > 1e-6)
+            .fold(f64::INFINITY, f64::min);
+        if !t.is_finite() {
+            return Intersect::empty();
+        }
+
+        let t = t as f32;
+        let punto = ray_origin + ray_direction * t;
+        let (u, v) = self.get_uv(&punto);
+        let normal = self.normal(&punto);
+        // u da la vuelta al eje Y y v a la sección del tubo, hacia arriba por el lado de afuera
+        let p = punto - self.centro;
+        let tangente = Vec3::new(p.z, 0.0, -p.x) * (2.0 * PI);
+        let bitangente = normal.cross(&tangente).normalize() * (2.0 * PI * self.radio_menor);
+        Intersect::new(punto, normal, t, self.material.clone(), u, v)
+            .con_tangentes(tangente, bitangente)
+    }
+
+    // u da la vuelta alrededor del eje Y y v alrededor del tubo
+    fn get_uv(&self, point: &Vec3) -> (f32, f32) {
+        let p = point - self.centro;
+        let distancia_al_eje = (p.x * p.x + p.z * p.z).sqrt();
+        let u = 0.5 - p.z.atan2(p.x) / (2.0 * PI);
+        let v = p.y.atan2(distancia_al_eje - self.radio_mayor) / (2.0 * PI) + 0.5;
+        (u, v)
+    }
+
+    fn aabb(&self) -> Aabb {
+        let horizontal = self.radio_mayor + self.radio_menor;
+        let extension = Vec3::new(horizontal, self.radio_menor, horizontal);
+        Aabb::new(self.centro - extension, self.centro + extension)
+    }
+}
+
+// Un par de pasos de Newton corrigen el error numérico de las fórmulas cerradas
+fn pulir_raiz(c: &[f64; 5], mut t: f64) -> f64 {
+    for _ in 0..2 {
+        let valor = (((c[4] * t + c[3]) * t + c[2]) * t + c[1]) * t + c[0];
+        let derivada = ((4.0 * c[4] * t + 3.0 * c[3]) * t + 2.0 * c[2]) * t + c[1];
+        if derivada.abs() < EPSILON_RAIZ {
+            break;
+        }
+        t -= valor / derivada;
+    }
+    t
+}
+
+// Los coeficientes van de menor a mayor grado: c[0] + c[1] x + c[2] x²
+fn resolver_cuadratica(c: &[f64; 3]) -> Vec<f64> {
+    let p = c[1] / (2.0 * c[2]);
+    let q = c[0] / c[2];
+    let discriminante = p * p - q;
+    if discriminante.abs() < EPSILON_RAIZ {
+        vec![-p]
+    } else if discriminante < 0.0 {
+        vec![]
+    } else {
+        let raiz = discriminante.sqrt();
+        vec![raiz - p, -raiz - p]
+    }
+}
+
+// Método de Cardano sobre la cúbica normalizada x³ + A x² + B x + C
+fn resolver_cubica(c: &[f64; 4]) -> Vec<f64> {
+    let a = c[2] / c[3];
+    let b = c[1] / c[3];
+    let cc = c[0] / c[3];
+
+    // Sustituyendo x = y - A/3 queda y³ + 3p y + 2q = 0
+    let a2 = a * a;
+    let p = (-a2 / 3.0 + b) / 3.0;
+    let q = (2.0 / 27.0 * a * a2 - a * b / 3.0 + cc) / 2.0;
+    let p3 = p * p * p;
+    let discriminante = q * q + p3;
+
+    let raices = if discriminante.abs() < EPSILON_RAIZ {
+        if q.abs() < EPSILON_RAIZ {
+            vec![0.0]
+        } else {
+            let u = (-q).cbrt();
+            vec![2.0 * u, -u]
+        }
+    } else if discriminante < 0.0 {
+        // Tres raíces reales
+        let phi = (-q / (-p3).sqrt()).clamp(-1.0, 1.0).acos() / 3.0;
+        let t = 2.0 * (-p).sqrt();
+        vec![
+            t * phi.cos(),
+            -t * (phi + std::f64::consts::PI / 3.0).cos(),
+            -t * (phi - std::f64::consts::PI / 3.0).cos(),
+        ]
+    } else {
+        let raiz = discriminante.sqrt();
+        vec![(raiz - q).cbrt() - (raiz + q).cbrt()]
+    };
+
+    raices.into_iter().map(|y| y - a / 3.0).collect()
+}
+
+// Método de Ferrari: reduce la cuártica a una cúbica resolvente y dos cuadráticas
+fn resolver_cuartica(c: &[f64; 5]) -> Vec<f64> {
+    let a = c[3] / c[4];
+    let b = c[2] / c[4];
+    let cc = c[1] / c[4];
+    let d = c[0] / c[4];
+
+    // Sustituyendo x = y - A/4 queda y⁴ + p y² + q y + r = 0
+    let a2 = a * a;
+    let p = -3.0 / 8.0 * a2 + b;
+    let q = a2 * a / 8.0 - a * b / 2.0 + cc;
+    let r = -3.0 / 256.0 * a2 * a2 + a2 * b / 16.0 - a * cc / 4.0 + d;
+
+    let mut raices = if r.abs() < EPSILON_RAIZ {
+        let mut raices = resolver_cubica(&[q, p, 0.0, 1.0]);
+        raices.push(0.0);
+        raices
+    } else {
+        let z = resolver_cubica(&[r * p / 2.0 - q * q / 8.0, -r, -p / 2.0, 1.0])[0];
+        let u = z * z - r;
+        let v = 2.0 * z - p;
+        let u = if u.abs() < EPSILON_RAIZ {
+            0.0
+        } else if u > 0.0 {
+            u.sqrt()
+        } else {
+            return vec![];
+        };
+        let v = if v.abs() < EPSILON_RAIZ {
+            0.0
+        } else if v > 0.0 {
+            v.sqrt()
+        } else {
+            return vec![];
+        };
+        let signo = if q < 0.0 { -1.0 } else { 1.0 };
+        let mut raices = resolver_cuadratica(&[z - u, signo * v, 1.0]);
+        raices.extend(resolver_cuadratica(&[z + u, -signo * v, 1.0]));
+        raices
+    };
+
+    for raiz in &mut raices {
+        *raiz -= a / 4.0;
+    }
+    raices
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+
+    fn toro() -> Torus {
+        let material = Material::new(Color::new(255, 255, 255), 10.0, [0.9, 0.1], None);
+        Torus::new(Vec3::zeros(), 2.0, 0.5, material)
+    }
+
+    #[test]
+    fn el_rayo_atraviesa_el_agujero() {
+        let toro = toro();
+
+        let por_el_centro =
+            toro.ray_intersect(&Vec3::new(0.0, 5.0, 0.0), &Vec3::new(0.0, -1.0, 0.0));
+        let sobre_el_tubo =
+            toro.ray_intersect(&Vec3::new(2.0, 5.0, 0.0), &Vec3::new(0.0, -1.0, 0.0));
+
+        assert!(!por_el_centro.is_intersecting);
+        assert!(
+            (sobre_el_tubo.distance - 4.5).abs() < 1e-4,
+            "{}",
+            sobre_el_tubo.distance
+        );
+        assert!((sobre_el_tubo.normal - Vec3::new(0.0, 1.0, 0.0)).magnitude() < 1e-4);
+    }
+
+    #[test]
+    fn de_lado_choca_con_el_borde_exterior() {
+        let hit = toro().ray_intersect(&Vec3::new(-10.0, 0.0, 0.0), &Vec3::new(1.0, 0.0, 0.0));
+
+        assert!((hit.distance - 7.5).abs() < 1e-4, "{}", hit.distance);
+        assert!((hit.normal - Vec3::new(-1.0, 0.0, 0.0)).magnitude() < 1e-4);
+        // Por fuera del tubo, a la altura del centro
+        assert!((hit.v - 0.5).abs() < 1e-4, "{}", hit.v);
+    }
+
+    #[test]
+    fn la_cuartica_encuentra_todas_las_raices() {
+        // (x - 1)(x - 2)(x - 3)(x - 4)
+        let mut raices = resolver_cuartica(&[24.0, -50.0, 35.0, -10.0, 1.0]);
+        raices.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        assert_eq!(raices.len(), 4);
+        for (raiz, esperada) in raices.iter().zip([1.0, 2.0, 3.0, 4.0]) {
+            assert!((raiz - esperada).abs() < 1e-6, "{:?}", raices);
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use crate::aabb::Aabb;
+use crate::ray_intersect::{Intersect, RayIntersect};
+use nalgebra_glm::{Mat4, Vec3, Vec4};
+
+// Envuelve un objeto y lo coloca en la escena con una matriz afín (traslación, rotación,
+// escala no uniforme). Los rayos se llevan al espacio del objeto con la inversa.
+pub struct Transform {
+    pub objeto: Box<dyn RayIntersect>,
+    matriz: Mat4,
+    inversa: Mat4,
+}
+
+impl Transform {
+    // Devuelve None si la matriz no es invertible (por ejemplo, escala cero en un eje)
+    pub fn new(objeto: Box<dyn RayIntersect>, matriz: Mat4) -> Option<Self> {
+        let inversa = matriz.try_inverse()?;
+        Some(Transform {
+            objeto,
+            matriz,
+            inversa,
+        })
+    }
+
+    // Traslada, luego rota (en grados, primero sobre X, después Y y al final Z) y escala
+    pub fn desde_partes(
+        objeto: Box<dyn RayIntersect>,
+        traslacion: Vec3,
+        rotacion_grados: Vec3,
+        escala: Vec3,
+    ) -> Option<Self> {
+        let rotacion =
+            nalgebra_glm::rotation(rotacion_grados.z.to_radians(), &Vec3::new(0.0, 0.0, 1.0))
+                * nalgebra_glm::rotation(rotacion_grados.y.to_radians(), &Vec3::new(0.0, 1.0, 0.0))
+                * nalgebra_glm::rotation(rotacion_grados.x.to_radians(), &Vec3::new(1.0, 0.0, 0.0));
+        let matriz =
+            nalgebra_glm::translation(&traslacion) * rotacion * nalgebra_glm::scaling(&escala);
+        Transform::new(objeto, matriz)
+    }
+
+    fn a_objeto(&self, punto: &Vec3) -> Vec3 {
+        (self.inversa * Vec4::new(punto.x, punto.y, punto.z, 1.0)).xyz()
+    }
+}
+
+impl RayIntersect for Transform {
+    fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Intersect {
+        // La dirección no se normaliza: así la distancia del choque en el espacio del objeto
+        // es la misma que en la escena
+        let origen = self.a_objeto(ray_origin);
+        let direccion = (self.inversa
+            * Vec4::new(ray_direction.x, ray_direction.y, ray_direction.z, 0.0))
+        .xyz();
+
+        let mut intersect = self.objeto.ray_intersect(&origen, &direccion);
+        if !intersect.is_intersecting {
+            return intersect;
+        }
+
+        intersect.point = ray_origin + ray_direction * intersect.distance;
+        // Las normales se transforman con la transpuesta de la inversa para que sigan siendo
+        // perpendiculares a la superficie cuando la escala no es uniforme
+        let n = intersect.normal;
+        intersect.normal = (self.inversa.transpose() * Vec4::new(n.x, n.y, n.z, 0.0))
+            .xyz()
+            .normalize();
+        // Las tangentes están sobre la superficie y se transforman como cualquier dirección
+        let direccion = |d: Vec3| (self.matriz * Vec4::new(d.x, d.y, d.z, 0.0)).xyz();
+        intersect.tangente = direccion(intersect.tangente);
+        intersect.bitangente = direccion(intersect.bitangente);
+        intersect
+    }
+
+    fn get_uv(&self, point: &Vec3) -> (f32, f32) {
+        self.objeto.get_uv(&self.a_objeto(point))
+    }
+
+    fn aabb(&self) -> Aabb {
+        let caja = self.objeto.aabb();
+        if !caja.es_finita() {
+            return Aabb::infinita();
+        }
+        // Caja que envuelve las ocho esquinas transformadas
+        (0..8).fold(Aabb::vacia(), |resultado, i| {
+            let esquina = Vec3::new(
+                if i & 1 == 0 { caja.min.x } else { caja.max.x },
+                if i & 2 == 0 { caja.min.y } else { caja.max.y },
+                if i & 4 == 0 { caja.min.z } else { caja.max.z },
+            );
+            let esquina = (self.matriz * Vec4::new(esquina.x, esquina.y, esquina.z, 1.0)).xyz();
+            resultado.incluir_punto(&esquina)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+    use crate::cube::Cube;
+    use crate::material::Material;
+    use crate::sphere::Sphere;
+
+    fn material() -> Material {
+        Material::new(Color::new(255, 255, 255), 10.0, [0.9, 0.1], None)
+    }
+
+    #[test]
+    fn la_escala_no_uniforme_estira_la_esfera() {
+        let esfera = Sphere {
+            center: Vec3::zeros(),
+            radius: 1.0,
+            material: material(),
+        };
+        let elipsoide = Transform::desde_partes(
+            Box::new(esfera),
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::zeros(),
+            Vec3::new(3.0, 1.0, 1.0),
+        )
+        .unwrap();
+
+        let de_lado =
+            elipsoide.ray_intersect(&Vec3::new(10.0, 1.0, 0.0), &Vec3::new(-1.0, 0.0, 0.0));
+        assert!(
+            (de_lado.distance - 7.0).abs() < 1e-4,
+            "{}",
+            de_lado.distance
+        );
+        assert!((de_lado.point - Vec3::new(3.0, 1.0, 0.0)).magnitude() < 1e-4);
+        assert!((de_lado.normal - Vec3::new(1.0, 0.0, 0.0)).magnitude() < 1e-4);
+
+        // En un punto inclinado la normal de la esfera estirada se aplana hacia el eje Y
+        let diagonal = Vec3::new(3.0, 1.0, 0.0).normalize();
+        let hit =
+            elipsoide.ray_intersect(&(Vec3::new(0.0, 1.0, 0.0) + diagonal * 10.0), &-diagonal);
+        let p = hit.point - Vec3::new(0.0, 1.0, 0.0);
+        let esperada = Vec3::new(p.x / 9.0, p.y, p.z).normalize();
+        assert!(
+            (hit.normal - esperada).magnitude() < 1e-4,
+            "{:?}",
+            hit.normal
+        );
+
+        let caja = elipsoide.aabb();
+        assert!((caja.max - Vec3::new(3.0, 2.0, 1.0)).magnitude() < 1e-4);
+    }
+
+    #[test]
+    fn un_cubo_rotado_muestra_su_arista() {
+        let cubo = Cube::new(Vec3::zeros(), 2.0, material(), Vec3::zeros());
+        let rotado = Transform::desde_partes(
+            Box::new(cubo),
+            Vec3::zeros(),
+            Vec3::new(0.0, 45.0, 0.0),
+            Vec3::repeat(1.0),
+        )
+        .unwrap();
+
+        // La arista queda a sqrt(2) del centro en lugar de a 1
+        let hit = rotado.ray_intersect(&Vec3::new(0.0, 0.0, 5.0), &Vec3::new(0.0, 0.0, -1.0));
+        assert!(
+            (hit.distance - (5.0 - 2.0_f32.sqrt())).abs() < 1e-3,
+            "{}",
+            hit.distance
+        );
+        let caja = rotado.aabb();
+        assert!((caja.max.x - 2.0_f32.sqrt()).abs() < 1e-4);
+        assert!((caja.max.y - 1.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn escala_cero_no_es_invertible() {
+        let cubo = Cube::new(Vec3::zeros(), 2.0, material(), Vec3::zeros());
+
+        let plano = Transform::desde_partes(
+            Box::new(cubo),
+            Vec3::zeros(),
+            Vec3::zeros(),
+            Vec3::new(1.0, 0.0, 1.0),
+        );
+
+        assert!(plano.is_none());
+    }
+}